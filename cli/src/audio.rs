@@ -0,0 +1,155 @@
+//! Audio output for the `audio` feature: a lock-protected stereo ring
+//! shared between the emulator thread (producer) and the cpal device
+//! callback (consumer). The ring builds unconditionally so its ordering
+//! and underrun behaviour are testable without a real device.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Shared stereo sample queue. Overrun drops the oldest samples to bound
+/// latency; underrun hands the device silence instead of stale data.
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub struct SampleRing {
+    samples: Mutex<VecDeque<(f32, f32)>>,
+    capacity: usize,
+}
+
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+impl SampleRing {
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        })
+    }
+
+    /// Producer side: append drained APU samples, discarding the oldest
+    /// on overflow so playback stays near real time.
+    pub fn push(&self, samples: &[(f32, f32)]) {
+        let mut queue = self.samples.lock().unwrap();
+        for &sample in samples {
+            if queue.len() == self.capacity {
+                queue.pop_front();
+            }
+            queue.push_back(sample);
+        }
+    }
+
+    /// Consumer side: fill an interleaved stereo buffer in FIFO order,
+    /// zero-filling whatever the ring cannot supply. Returns the number of
+    /// frames that came from the ring.
+    pub fn fill(&self, out: &mut [f32]) -> usize {
+        let mut queue = self.samples.lock().unwrap();
+        let mut supplied = 0;
+        for frame in out.chunks_exact_mut(2) {
+            if let Some((left, right)) = queue.pop_front() {
+                frame[0] = left;
+                frame[1] = right;
+                supplied += 1;
+            } else {
+                frame[0] = 0.0;
+                frame[1] = 0.0;
+            }
+        }
+        supplied
+    }
+}
+
+#[cfg(feature = "audio")]
+pub use backend::AudioOutput;
+
+#[cfg(feature = "audio")]
+mod backend {
+    use std::sync::Arc;
+
+    use anyhow::{Context, Result};
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    use super::SampleRing;
+
+    /// A playing cpal output stream fed from a [`SampleRing`].
+    pub struct AudioOutput {
+        ring: Arc<SampleRing>,
+        sample_rate: usize,
+        _stream: cpal::Stream,
+    }
+
+    impl AudioOutput {
+        /// Open the default output device at its default rate, stereo.
+        pub fn start() -> Result<Self> {
+            let host = cpal::default_host();
+            let device = host
+                .default_output_device()
+                .context("no audio output device")?;
+            let config = device
+                .default_output_config()
+                .context("no default output config")?;
+            let sample_rate = config.sample_rate().0 as usize;
+            // A quarter second of buffer absorbs frame-time jitter.
+            let ring = SampleRing::new(sample_rate / 4);
+            let consumer = Arc::clone(&ring);
+            let stream = device.build_output_stream(
+                &cpal::StreamConfig {
+                    channels: 2,
+                    sample_rate: config.sample_rate(),
+                    buffer_size: cpal::BufferSize::Default,
+                },
+                move |data: &mut [f32], _| {
+                    consumer.fill(data);
+                },
+                |err| eprintln!("audio stream error: {err}"),
+                None,
+            )?;
+            stream.play()?;
+            Ok(Self {
+                ring,
+                sample_rate,
+                _stream: stream,
+            })
+        }
+
+        /// The device rate, to pass to [`core_lib::apu::Apu::drain`].
+        pub fn sample_rate(&self) -> usize {
+            self.sample_rate
+        }
+
+        /// Queue a batch of drained APU samples for the device callback.
+        pub fn queue(&self, samples: &[(f32, f32)]) {
+            self.ring.push(samples);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_ring_preserves_sample_order_across_partial_reads() {
+        let ring = SampleRing::new(8);
+        ring.push(&[(1.0, -1.0), (2.0, -2.0), (3.0, -3.0)]);
+
+        let mut out = [9.0f32; 4]; // two frames
+        assert_eq!(ring.fill(&mut out), 2);
+        assert_eq!(out, [1.0, -1.0, 2.0, -2.0]);
+
+        ring.push(&[(4.0, -4.0)]);
+        let mut out = [9.0f32; 4];
+        assert_eq!(ring.fill(&mut out), 2, "the leftover then the new sample");
+        assert_eq!(out, [3.0, -3.0, 4.0, -4.0]);
+    }
+
+    #[test]
+    fn underrun_fills_with_silence_and_overrun_drops_the_oldest() {
+        let ring = SampleRing::new(2);
+
+        let mut out = [9.0f32; 4];
+        assert_eq!(ring.fill(&mut out), 0, "empty ring supplies nothing");
+        assert_eq!(out, [0.0; 4], "underrun is silence, not stale data");
+
+        ring.push(&[(1.0, 1.0), (2.0, 2.0), (3.0, 3.0)]); // capacity 2
+        let mut out = [9.0f32; 4];
+        assert_eq!(ring.fill(&mut out), 2);
+        assert_eq!(out, [2.0, 2.0, 3.0, 3.0], "the oldest sample was dropped");
+    }
+}
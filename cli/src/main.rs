@@ -0,0 +1,858 @@
+//! Command-line frontend: run a ROM in a window, headless, or as a test ROM.
+
+mod audio;
+
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::{Window, WindowId};
+
+use core_lib::input::{InputPlayback, InputRecorder};
+use core_lib::ppu::{DmgPalette, SCREEN_HEIGHT, SCREEN_WIDTH};
+use core_lib::{Cartridge, Cpu, Mmu};
+
+/// One DMG frame: 70224 dots at 4.194304 MHz (~59.73 Hz).
+const FRAME_DURATION: Duration = Duration::from_nanos(16_742_706);
+
+#[derive(Parser)]
+#[command(name = "gboxide", about = "A Game Boy emulator")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a ROM.
+    Run {
+        rom: PathBuf,
+        /// Run without opening a window.
+        #[arg(long)]
+        headless: bool,
+        /// Print CPU trace output.
+        #[arg(long)]
+        debug: bool,
+        /// Directory for .sav files (default: next to the ROM).
+        #[arg(long)]
+        save_dir: Option<PathBuf>,
+        /// Disable the ~59.7 Hz frame limiter (benchmarking).
+        #[arg(long)]
+        uncapped: bool,
+        /// Replay a recorded input log instead of taking live input.
+        #[arg(long)]
+        replay: Option<PathBuf>,
+        /// Colour scheme: "grey", "green", or four RRGGBB values
+        /// (lightest first) separated by commas.
+        #[arg(long, default_value = "grey")]
+        palette: String,
+        /// Drop into an interactive debugger instead of running freely.
+        #[arg(long)]
+        debug_repl: bool,
+        /// Map this 256-byte DMG boot ROM and start at 0x0000. Without it
+        /// the boot sequence is skipped: post-boot registers, PC=0x0100.
+        #[arg(long)]
+        bootrom: Option<PathBuf>,
+        /// Survey mode: run unknown opcodes as NOPs and report each
+        /// distinct one with its PC and hit count instead of aborting.
+        #[arg(long)]
+        trace_unimplemented: bool,
+    },
+    /// Run a test ROM and report pass/fail from its serial output.
+    Test { rom: PathBuf },
+    /// Run a ROM headless for some frames and write a PNG screenshot.
+    Screenshot {
+        rom: PathBuf,
+        /// Frames to emulate before capturing.
+        #[arg(long, default_value_t = 60)]
+        frames: u64,
+        /// Output file (default: the ROM path with a .png extension).
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Run every .gb ROM in a directory and print a pass/fail summary.
+    TestSuite {
+        dir: PathBuf,
+        /// Per-ROM cycle budget before a run counts as a timeout.
+        #[arg(long, default_value_t = 10_000_000)]
+        timeout_cycles: usize,
+    },
+}
+
+fn main() -> Result<()> {
+    common::logging::init();
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run {
+            rom,
+            headless,
+            debug,
+            save_dir,
+            uncapped,
+            replay,
+            palette,
+            debug_repl,
+            bootrom,
+            trace_unimplemented,
+        } => {
+            let palette = parse_palette(&palette)?;
+            if debug_repl {
+                run_debug_repl(&rom, save_dir.as_deref(), bootrom.as_deref())
+            } else if headless {
+                run_rom_headless(
+                    &rom,
+                    debug,
+                    save_dir.as_deref(),
+                    bootrom.as_deref(),
+                    trace_unimplemented,
+                )
+            } else {
+                run_rom(
+                    &rom,
+                    debug,
+                    save_dir.as_deref(),
+                    uncapped,
+                    replay.as_deref(),
+                    palette,
+                    bootrom.as_deref(),
+                    trace_unimplemented,
+                )
+            }
+        }
+        Command::Test { rom } => run_test_rom(&rom),
+        Command::Screenshot {
+            rom,
+            frames,
+            output,
+        } => run_screenshot(&rom, frames, output),
+        Command::TestSuite {
+            dir,
+            timeout_cycles,
+        } => run_test_suite(&dir, timeout_cycles),
+    }
+}
+
+fn load_cartridge(path: &Path) -> Result<Cartridge> {
+    Cartridge::from_path(path).with_context(|| format!("failed to load ROM {}", path.display()))
+}
+
+/// Parse a `--palette` value: a named preset or four comma-separated
+/// RRGGBB colours, lightest first.
+fn parse_palette(value: &str) -> Result<DmgPalette> {
+    match value {
+        "grey" | "gray" => return Ok(DmgPalette::GREY),
+        "green" => return Ok(DmgPalette::LCD_GREEN),
+        _ => {}
+    }
+    let parts: Vec<&str> = value.split(',').collect();
+    if parts.len() != 4 {
+        anyhow::bail!("palette must be \"grey\", \"green\" or four RRGGBB values");
+    }
+    let mut colors = [common::Color::new(0, 0, 0); 4];
+    for (slot, part) in colors.iter_mut().zip(&parts) {
+        let rgb = u32::from_str_radix(part, 16)
+            .with_context(|| format!("invalid palette colour {part:?}"))?;
+        if rgb > 0xFF_FF_FF {
+            anyhow::bail!("palette colour {part:?} is more than six hex digits");
+        }
+        *slot = common::Color::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8);
+    }
+    Ok(DmgPalette::uniform(colors))
+}
+
+/// Focus-state to mute mapping: audio is muted exactly while the window
+/// is unfocused, so background noise stops without detaching the sink.
+fn apply_focus(mmu: &mut Mmu, focused: bool) {
+    mmu.apu.set_muted(!focused);
+}
+
+/// Where the ROM's battery save lives: `<rom>.sav`, or the same file name
+/// under `save_dir` when given.
+fn sav_path(rom: &Path, save_dir: Option<&Path>) -> PathBuf {
+    let mut path = match save_dir {
+        Some(dir) => dir.join(rom.file_name().unwrap_or_default()),
+        None => rom.to_path_buf(),
+    };
+    path.set_extension("sav");
+    path
+}
+
+/// Load a cartridge and, when it is battery-backed and a .sav file exists,
+/// install the saved RAM before the first step.
+fn load_cartridge_with_save(rom: &Path, save_dir: Option<&Path>) -> Result<Cartridge> {
+    let mut cart = load_cartridge(rom)?;
+    if cart.has_battery() {
+        let sav = sav_path(rom, save_dir);
+        if let Ok(ram) = std::fs::read(&sav) {
+            cart.install_ram(ram)
+                .with_context(|| format!("save file {} does not fit this ROM", sav.display()))?;
+        }
+    }
+    Ok(cart)
+}
+
+/// Write battery-backed RAM out to the .sav file. No-op for carts without
+/// a battery.
+fn persist_save(rom: &Path, save_dir: Option<&Path>, cart: &Cartridge) -> Result<()> {
+    if !cart.has_battery() {
+        return Ok(());
+    }
+    let sav = sav_path(rom, save_dir);
+    std::fs::write(&sav, cart.ram())
+        .with_context(|| format!("failed to write save file {}", sav.display()))
+}
+
+/// Build the CPU and MMU in an explicit start state: with a boot ROM, map
+/// it and start at 0x0000; without one, skip the boot sequence with the
+/// post-boot register state and PC=0x0100.
+fn boot_machine(cart: Cartridge, bootrom: Option<&Path>) -> Result<(Cpu, Mmu)> {
+    match bootrom {
+        Some(path) => {
+            let boot = std::fs::read(path)
+                .with_context(|| format!("failed to read boot ROM {}", path.display()))?;
+            if boot.len() != 0x100 {
+                anyhow::bail!("boot ROM must be 256 bytes, got {}", boot.len());
+            }
+            let mut cpu = Cpu::new();
+            cpu.regs.pc = 0x0000;
+            Ok((cpu, Mmu::with_boot_rom(cart, boot)))
+        }
+        None => {
+            let mut cpu = Cpu::new();
+            cpu.reset_post_boot();
+            cpu.regs.pc = 0x0100;
+            Ok((cpu, Mmu::new(cart)))
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_rom(
+    path: &Path,
+    debug: bool,
+    save_dir: Option<&Path>,
+    uncapped: bool,
+    replay: Option<&Path>,
+    palette: DmgPalette,
+    bootrom: Option<&Path>,
+    trace_unimplemented: bool,
+) -> Result<()> {
+    let cart = load_cartridge_with_save(path, save_dir)?;
+    let (mut cpu, mut mmu) = boot_machine(cart, bootrom)?;
+    cpu.trace = debug;
+    cpu.log_unimplemented = trace_unimplemented;
+    mmu.ppu.set_dmg_palette(palette);
+    let playback = replay.map(load_replay).transpose()?;
+
+    #[cfg(feature = "audio")]
+    let audio = match audio::AudioOutput::start() {
+        Ok(output) => Some(output),
+        Err(err) => {
+            eprintln!("audio disabled: {err}");
+            None
+        }
+    };
+
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+    let mut app = EmulatorApp {
+        cpu,
+        mmu,
+        window: None,
+        surface: None,
+        uncapped,
+        next_frame: Instant::now(),
+        playback,
+        #[cfg(feature = "audio")]
+        audio,
+    };
+    event_loop.run_app(&mut app)?;
+    print_unimplemented_report(&app.cpu);
+    persist_save(path, save_dir, app.mmu.cartridge())
+}
+
+/// Print the opcode-coverage tally gathered under `--trace-unimplemented`.
+fn print_unimplemented_report(cpu: &Cpu) {
+    for (opcode, pc, count) in cpu.unimplemented_report() {
+        println!("unimplemented opcode {opcode:#04X} first hit at {pc:#06X}, {count} time(s)");
+    }
+}
+
+struct EmulatorApp {
+    cpu: Cpu,
+    mmu: Mmu,
+    window: Option<Rc<Window>>,
+    surface: Option<softbuffer::Surface<Rc<Window>, Rc<Window>>>,
+    uncapped: bool,
+    /// Deadline the current frame should be presented at.
+    next_frame: Instant,
+    /// Recorded inputs being replayed, if `--replay` was given.
+    playback: Option<InputPlayback>,
+    /// The cpal stream APU samples are queued into, when `audio` is built
+    /// in and a device opened.
+    #[cfg(feature = "audio")]
+    audio: Option<audio::AudioOutput>,
+}
+
+/// Read an input log recorded in the core's compact format.
+fn load_replay(path: &Path) -> Result<InputPlayback> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("failed to read replay {}", path.display()))?;
+    Ok(InputRecorder::from_bytes(&bytes)?.into_playback())
+}
+
+impl EmulatorApp {
+    /// Blit the PPU framebuffer to the window, nearest-neighbour scaled.
+    fn present(&mut self) {
+        let (Some(window), Some(surface)) = (&self.window, &mut self.surface) else {
+            return;
+        };
+        let size = window.inner_size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            return;
+        };
+        if surface.resize(width, height).is_err() {
+            return;
+        }
+        let Ok(mut buffer) = surface.buffer_mut() else {
+            return;
+        };
+        let frame = self.mmu.frame_buffer();
+        let palette = self.mmu.ppu.dmg_palette();
+        let (width, height) = (size.width as usize, size.height as usize);
+        for wy in 0..height {
+            let sy = wy * SCREEN_HEIGHT / height;
+            for wx in 0..width {
+                let sx = wx * SCREEN_WIDTH / width;
+                let shade = frame[sy * SCREEN_WIDTH + sx];
+                buffer[wy * width + wx] = palette.bg[(shade & 0x03) as usize].to_rgb_u32();
+            }
+        }
+        let _ = buffer.present();
+    }
+}
+
+impl ApplicationHandler for EmulatorApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_none() {
+            let attrs = Window::default_attributes()
+                .with_title("gboxide")
+                .with_inner_size(LogicalSize::new(
+                    SCREEN_WIDTH as u32 * 3,
+                    SCREEN_HEIGHT as u32 * 3,
+                ));
+            let window = Rc::new(event_loop.create_window(attrs).expect("create window"));
+            let context = softbuffer::Context::new(Rc::clone(&window)).expect("create context");
+            self.surface = Some(
+                softbuffer::Surface::new(&context, Rc::clone(&window)).expect("create surface"),
+            );
+            self.window = Some(window);
+            self.next_frame = Instant::now() + FRAME_DURATION;
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Focused(focused) => apply_focus(&mut self.mmu, focused),
+            WindowEvent::RedrawRequested => self.present(),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Deliver any replayed inputs due this frame before emulating it.
+        if let Some(playback) = &mut self.playback {
+            let frame = self.mmu.frames_rendered();
+            playback.apply(&mut self.mmu, frame);
+        }
+
+        // Emulate exactly one frame per wakeup.
+        let target = self.mmu.frames_rendered() + 1;
+        while self.mmu.frames_rendered() < target {
+            let result = self
+                .cpu
+                .step(&mut self.mmu)
+                .and_then(|cycles| Ok(self.mmu.step(cycles)?));
+            if let Err(err) = result {
+                eprintln!("emulation stopped: {err}");
+                event_loop.exit();
+                return;
+            }
+        }
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &self.audio {
+            audio.queue(&self.mmu.apu.drain(audio.sample_rate()));
+        }
+
+        self.present();
+
+        if !self.uncapped {
+            let now = Instant::now();
+            if let Some(wait) = self.next_frame.checked_duration_since(now) {
+                std::thread::sleep(wait);
+            }
+            self.next_frame += FRAME_DURATION;
+            // If we fell behind (slow host, window drag), don't try to
+            // catch up by bursting frames.
+            if self.next_frame < Instant::now() {
+                self.next_frame = Instant::now() + FRAME_DURATION;
+            }
+        }
+    }
+}
+
+fn run_rom_headless(
+    path: &Path,
+    debug: bool,
+    save_dir: Option<&Path>,
+    bootrom: Option<&Path>,
+    trace_unimplemented: bool,
+) -> Result<()> {
+    let cart = load_cartridge_with_save(path, save_dir)?;
+    let (mut cpu, mut mmu) = boot_machine(cart, bootrom)?;
+    cpu.trace = debug;
+    cpu.log_unimplemented = trace_unimplemented;
+    mmu.set_serial_instant(true);
+
+    let result = run_until(&mut cpu, &mut mmu, StopCondition::Instructions(10_000))?;
+    println!("ran {} cycles", result.cycles);
+    if !mmu.serial.output.is_empty() {
+        println!("serial: {}", String::from_utf8_lossy(&mmu.serial.output));
+    }
+    print_unimplemented_report(&cpu);
+    persist_save(path, save_dir, mmu.cartridge())
+}
+
+/// Interactive debugger: read commands from stdin, print state to stdout.
+fn run_debug_repl(path: &Path, save_dir: Option<&Path>, bootrom: Option<&Path>) -> Result<()> {
+    use std::io::Write;
+
+    let cart = load_cartridge_with_save(path, save_dir)?;
+    let (cpu, mmu) = boot_machine(cart, bootrom)?;
+    let mut debugger = core_lib::Debugger::new(core_lib::System::from_parts(cpu, mmu));
+    debugger.system.mmu.set_serial_instant(true);
+
+    println!("commands: b <addr>  d <addr>  c  s  x <addr>  regs  q");
+    let mut line = String::new();
+    loop {
+        print!("(gboxide) ");
+        std::io::stdout().flush()?;
+        line.clear();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("b"), Some(addr)) => {
+                let addr = parse_addr(addr)?;
+                debugger.add_breakpoint(addr);
+                println!("breakpoint at {addr:#06x}");
+            }
+            (Some("d"), Some(addr)) => {
+                let addr = parse_addr(addr)?;
+                if !debugger.remove_breakpoint(addr) {
+                    println!("no breakpoint at {addr:#06x}");
+                }
+            }
+            (Some("c"), _) => {
+                let pc = debugger.continue_until_break()?;
+                println!("break at {pc:#06x}");
+            }
+            (Some("s"), _) => {
+                debugger.step()?;
+                println!("pc = {:#06x}", debugger.registers().pc);
+            }
+            (Some("x"), Some(addr)) => {
+                let addr = parse_addr(addr)?;
+                println!("{addr:#06x}: {:#04x}", debugger.read_memory(addr));
+            }
+            (Some("regs"), _) => {
+                let r = debugger.registers();
+                println!(
+                    "af={:02x}{:02x} bc={:02x}{:02x} de={:02x}{:02x} hl={:02x}{:02x} sp={:04x} pc={:04x}",
+                    r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l, r.sp, r.pc
+                );
+            }
+            (Some("q"), _) => break,
+            (None, _) => {}
+            (Some(cmd), _) => println!("unknown command {cmd:?}"),
+        }
+    }
+    persist_save(path, save_dir, debugger.system.mmu.cartridge())
+}
+
+/// Parse a debugger address: hex with or without a `0x` prefix.
+fn parse_addr(word: &str) -> Result<u16> {
+    let digits = word.trim_start_matches("0x").trim_start_matches("0X");
+    u16::from_str_radix(digits, 16).with_context(|| format!("invalid address {word:?}"))
+}
+
+/// Emulate `frames` frames and write the final frame as an RGBA PNG.
+fn run_screenshot(path: &Path, frames: u64, output: Option<PathBuf>) -> Result<()> {
+    let mut system = core_lib::System::new(load_cartridge(path)?);
+    for _ in 0..frames {
+        system.run_frame()?;
+    }
+    let rgba = system.mmu.ppu.framebuffer_rgba();
+
+    let output = output.unwrap_or_else(|| path.with_extension("png"));
+    let file = std::fs::File::create(&output)
+        .with_context(|| format!("failed to create {}", output.display()))?;
+    let mut encoder = png::Encoder::new(
+        std::io::BufWriter::new(file),
+        SCREEN_WIDTH as u32,
+        SCREEN_HEIGHT as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&rgba)?;
+    println!("wrote {}", output.display());
+    Ok(())
+}
+
+/// When [`run_until`] gives up stepping.
+#[derive(Debug, Clone, Copy)]
+enum StopCondition {
+    /// After this many instructions (the interactive headless cap).
+    Instructions(usize),
+    /// After this many cycles, or as soon as the captured serial output
+    /// contains a "Passed"/"Failed" verdict.
+    CyclesOrVerdict(usize),
+}
+
+/// What [`run_until`] did before stopping.
+struct RunResult {
+    cycles: usize,
+    /// The program reached a HALT opcode.
+    halted: bool,
+}
+
+/// The shared headless stepping loop: fetch, stop on HALT, step CPU and
+/// peripherals, and accumulate cycles until `stop` is met. Serial output
+/// collects in `mmu.serial.output` as usual.
+fn run_until(cpu: &mut Cpu, mmu: &mut Mmu, stop: StopCondition) -> Result<RunResult> {
+    let mut cycles_run = 0usize;
+    let mut instructions = 0usize;
+    let mut halted = false;
+    loop {
+        match stop {
+            StopCondition::Instructions(max) if instructions >= max => break,
+            StopCondition::CyclesOrVerdict(max) if cycles_run >= max => break,
+            _ => {}
+        }
+        if mmu.read(cpu.regs.pc) == 0x76 {
+            halted = true;
+            break;
+        }
+        let cycles = cpu.step(mmu)?;
+        mmu.step(cycles)?;
+        cycles_run += cycles;
+        instructions += 1;
+        if matches!(stop, StopCondition::CyclesOrVerdict(_)) {
+            let output = String::from_utf8_lossy(&mmu.serial.output);
+            if output.contains("Passed") || output.contains("Failed") {
+                break;
+            }
+        }
+    }
+    Ok(RunResult {
+        cycles: cycles_run,
+        halted,
+    })
+}
+
+/// Result of running one test ROM to completion or its cycle budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestOutcome {
+    Passed,
+    Failed,
+    Timeout,
+}
+
+impl TestOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Passed => "pass",
+            Self::Failed => "FAIL",
+            Self::Timeout => "timeout",
+        }
+    }
+}
+
+/// Run a test cartridge until its serial output reports a verdict, it halts,
+/// or the cycle budget runs out.
+fn run_test_cartridge(cart: Cartridge, max_cycles: usize) -> Result<(TestOutcome, String)> {
+    let (mut cpu, mut mmu) = boot_machine(cart, None)?;
+    mmu.set_serial_instant(true);
+
+    let result = run_until(&mut cpu, &mut mmu, StopCondition::CyclesOrVerdict(max_cycles))?;
+
+    let output = String::from_utf8_lossy(&mmu.serial.output).into_owned();
+    let outcome = if output.contains("Passed") {
+        TestOutcome::Passed
+    } else if output.contains("Failed") || result.halted {
+        TestOutcome::Failed
+    } else {
+        TestOutcome::Timeout
+    };
+    Ok((outcome, output))
+}
+
+fn run_test_rom(path: &Path) -> Result<()> {
+    let cart = load_cartridge(path)?;
+    let (outcome, output) = run_test_cartridge(cart, 10_000_000)?;
+    println!("{output}");
+    if outcome == TestOutcome::Passed {
+        Ok(())
+    } else {
+        anyhow::bail!("test ROM did not pass ({})", outcome.label())
+    }
+}
+
+/// Tally of a suite run, printed as the summary footer.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SuiteSummary {
+    passed: usize,
+    failed: usize,
+    timed_out: usize,
+}
+
+impl SuiteSummary {
+    fn record(&mut self, outcome: TestOutcome) {
+        match outcome {
+            TestOutcome::Passed => self.passed += 1,
+            TestOutcome::Failed => self.failed += 1,
+            TestOutcome::Timeout => self.timed_out += 1,
+        }
+    }
+
+    fn all_passed(&self) -> bool {
+        self.failed == 0 && self.timed_out == 0
+    }
+}
+
+fn run_test_suite(dir: &Path, timeout_cycles: usize) -> Result<()> {
+    let mut roms: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "gb"))
+        .collect();
+    roms.sort();
+    if roms.is_empty() {
+        anyhow::bail!("no .gb ROMs found in {}", dir.display());
+    }
+
+    let mut summary = SuiteSummary::default();
+    for rom in &roms {
+        let name = rom.file_name().unwrap_or_default().to_string_lossy();
+        let outcome = match load_cartridge(rom).and_then(|c| run_test_cartridge(c, timeout_cycles))
+        {
+            Ok((outcome, _)) => outcome,
+            Err(err) => {
+                eprintln!("{name}: error: {err}");
+                TestOutcome::Failed
+            }
+        };
+        summary.record(outcome);
+        println!("{name:<40} {}", outcome.label());
+    }
+    println!(
+        "\n{} passed, {} failed, {} timed out",
+        summary.passed, summary.failed, summary.timed_out
+    );
+    if summary.all_passed() {
+        Ok(())
+    } else {
+        anyhow::bail!("test suite had failures")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom_with_program(program: &[u8]) -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x100 + program.len()].copy_from_slice(program);
+        rom
+    }
+
+    /// Program that prints `text` over serial then spins.
+    fn serial_print_rom(text: &str) -> Vec<u8> {
+        let mut program = Vec::new();
+        for byte in text.bytes() {
+            program.extend_from_slice(&[
+                0x3E, byte, // LD A,ch
+                0xE0, 0x01, // LDH (SB),A
+                0x3E, 0x81, // LD A,0x81
+                0xE0, 0x02, // LDH (SC),A
+            ]);
+        }
+        program.extend_from_slice(&[0x18, 0xFE]); // JR -2
+        rom_with_program(&program)
+    }
+
+    #[test]
+    fn boot_machine_without_a_bootrom_starts_post_boot() {
+        let cart = Cartridge::new(rom_with_program(&[])).unwrap();
+        let (cpu, mmu) = boot_machine(cart, None).unwrap();
+        assert_eq!(cpu.regs.pc, 0x0100);
+        assert_eq!(cpu.regs.a, 0x01, "post-boot register state");
+        assert!(!mmu.boot_rom_mapped());
+    }
+
+    #[test]
+    fn boot_machine_maps_a_256_byte_bootrom_and_starts_at_zero() {
+        let dir = std::env::temp_dir().join(format!("gboxide-boot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let boot_path = dir.join("dmg_boot.bin");
+        let mut boot = vec![0u8; 0x100];
+        boot[0] = 0x42;
+        std::fs::write(&boot_path, &boot).unwrap();
+
+        let cart = Cartridge::new(rom_with_program(&[])).unwrap();
+        let (cpu, mmu) = boot_machine(cart, Some(&boot_path)).unwrap();
+        assert_eq!(cpu.regs.pc, 0x0000);
+        assert!(mmu.boot_rom_mapped());
+        assert_eq!(mmu.read(0x0000), 0x42, "the boot ROM overlays the cart");
+
+        // Anything but exactly 256 bytes is rejected up front.
+        std::fs::write(&boot_path, [0u8; 0x80]).unwrap();
+        let cart = Cartridge::new(rom_with_program(&[])).unwrap();
+        let err = match boot_machine(cart, Some(&boot_path)) {
+            Err(err) => err,
+            Ok(_) => panic!("a short boot ROM must be rejected"),
+        };
+        assert!(err.to_string().contains("256 bytes"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn post_boot_machine(rom: Vec<u8>) -> (Cpu, Mmu) {
+        let mut cpu = Cpu::new();
+        cpu.reset_post_boot();
+        let mut mmu = Mmu::new(Cartridge::new(rom).unwrap());
+        mmu.set_serial_instant(true);
+        (cpu, mmu)
+    }
+
+    #[test]
+    fn run_until_stops_after_the_instruction_budget() {
+        // An infinite JR loop: only the budget can stop it.
+        let (mut cpu, mut mmu) = post_boot_machine(rom_with_program(&[0x18, 0xFE]));
+        let result = run_until(&mut cpu, &mut mmu, StopCondition::Instructions(5)).unwrap();
+        assert_eq!(result.cycles, 5 * 12, "five JRs, 12 cycles each");
+        assert!(!result.halted);
+    }
+
+    #[test]
+    fn run_until_stops_on_a_serial_verdict_before_the_cycle_cap() {
+        let (mut cpu, mut mmu) = post_boot_machine(serial_print_rom("Passed"));
+        let result = run_until(&mut cpu, &mut mmu, StopCondition::CyclesOrVerdict(1_000_000)).unwrap();
+        assert!(result.cycles < 10_000, "stopped at the verdict, not the cap");
+        assert_eq!(String::from_utf8_lossy(&mmu.serial.output), "Passed");
+    }
+
+    #[test]
+    fn trace_unimplemented_tallies_unknown_opcodes_and_continues() {
+        // 0xD3 is not an SM83 instruction; hit it twice, then HALT.
+        let (mut cpu, mut mmu) = post_boot_machine(rom_with_program(&[0xD3, 0x00, 0xD3, 0x76]));
+        cpu.log_unimplemented = true;
+        let result = run_until(&mut cpu, &mut mmu, StopCondition::Instructions(100)).unwrap();
+        assert!(result.halted, "the ROM ran past the unknown opcodes");
+        assert_eq!(cpu.unimplemented_report(), vec![(0xD3, 0x0100, 2)]);
+    }
+
+    #[test]
+    fn run_until_reports_reaching_a_halt() {
+        let (mut cpu, mut mmu) = post_boot_machine(rom_with_program(&[0x00, 0x76]));
+        let result = run_until(&mut cpu, &mut mmu, StopCondition::Instructions(100)).unwrap();
+        assert!(result.halted);
+        assert_eq!(result.cycles, 4, "one NOP before the HALT");
+    }
+
+    #[test]
+    fn battery_ram_round_trips_through_the_sav_file() {
+        let dir = std::env::temp_dir().join(format!("gboxide-sav-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rom_path = dir.join("game.gb");
+        let mut rom = rom_with_program(&[]);
+        rom[0x147] = 0x03; // MBC1 + RAM + battery
+        rom[0x149] = 0x02; // 8 KiB
+        std::fs::write(&rom_path, &rom).unwrap();
+
+        let mut mmu = Mmu::new(load_cartridge_with_save(&rom_path, None).unwrap());
+        mmu.write(0x0000, 0x0A); // enable RAM
+        mmu.write(0xA000, 0x77);
+        persist_save(&rom_path, None, mmu.cartridge()).unwrap();
+
+        let mut mmu = Mmu::new(load_cartridge_with_save(&rom_path, None).unwrap());
+        mmu.write(0x0000, 0x0A);
+        assert_eq!(mmu.read(0xA000), 0x77);
+
+        // A truncated save must produce an error, not a panic.
+        std::fs::write(sav_path(&rom_path, None), [0u8; 16]).unwrap();
+        assert!(load_cartridge_with_save(&rom_path, None).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn palette_arg_accepts_presets_and_custom_colours() {
+        assert_eq!(parse_palette("grey").unwrap(), DmgPalette::GREY);
+        assert_eq!(parse_palette("green").unwrap(), DmgPalette::LCD_GREEN);
+
+        let custom = parse_palette("FFFFFF,AAAAAA,555555,000000").unwrap();
+        assert_eq!(custom, DmgPalette::GREY);
+
+        assert!(parse_palette("turquoise").is_err());
+        assert!(parse_palette("FFFFFF,AAAAAA").is_err());
+        assert!(parse_palette("FFFFFF,AAAAAA,555555,1000000").is_err());
+    }
+
+    #[test]
+    fn focus_loss_mutes_the_apu_until_focus_returns() {
+        let mut mmu = Mmu::new(Cartridge::new(rom_with_program(&[])).unwrap());
+        assert!(!mmu.apu.muted());
+        apply_focus(&mut mmu, false);
+        assert!(mmu.apu.muted());
+        apply_focus(&mut mmu, true);
+        assert!(!mmu.apu.muted());
+    }
+
+    #[test]
+    fn suite_summary_counts_pass_and_timeout() {
+        let passing = Cartridge::new(serial_print_rom("Passed")).unwrap();
+        let spinning = Cartridge::new(rom_with_program(&[0x18, 0xFE])).unwrap();
+
+        let mut summary = SuiteSummary::default();
+        let (outcome, output) = run_test_cartridge(passing, 1_000_000).unwrap();
+        assert_eq!(outcome, TestOutcome::Passed);
+        assert!(output.contains("Passed"));
+        summary.record(outcome);
+
+        let (outcome, _) = run_test_cartridge(spinning, 100_000).unwrap();
+        assert_eq!(outcome, TestOutcome::Timeout);
+        summary.record(outcome);
+
+        assert_eq!(
+            summary,
+            SuiteSummary {
+                passed: 1,
+                failed: 0,
+                timed_out: 1
+            }
+        );
+        assert!(!summary.all_passed());
+    }
+}
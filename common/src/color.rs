@@ -0,0 +1,67 @@
+//! RGB colour type shared between the PPU and the frontends.
+
+/// A 24-bit RGB colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const WHITE: Self = Self::new(0xFF, 0xFF, 0xFF);
+    pub const BLACK: Self = Self::new(0x00, 0x00, 0x00);
+
+    #[must_use]
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Map a DMG 2-bit shade (0 = lightest, 3 = darkest) to a grey colour.
+    #[must_use]
+    pub const fn from_dmg_shade(shade: u8) -> Self {
+        match shade & 0x03 {
+            0 => Self::new(0xFF, 0xFF, 0xFF),
+            1 => Self::new(0xAA, 0xAA, 0xAA),
+            2 => Self::new(0x55, 0x55, 0x55),
+            _ => Self::new(0x00, 0x00, 0x00),
+        }
+    }
+
+    /// Pack into `0x00RRGGBB`.
+    #[must_use]
+    pub const fn to_rgb_u32(self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    }
+
+    /// Pack into 16-bit RGB565 (5 bits red, 6 green, 5 blue).
+    #[must_use]
+    pub const fn to_rgb565(self) -> u16 {
+        (((self.r as u16) >> 3) << 11) | (((self.g as u16) >> 2) << 5) | ((self.b as u16) >> 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dmg_shades_are_monotonic_greys() {
+        assert_eq!(Color::from_dmg_shade(0), Color::WHITE);
+        assert_eq!(Color::from_dmg_shade(3), Color::BLACK);
+        assert_eq!(Color::from_dmg_shade(1).r, 0xAA);
+    }
+
+    #[test]
+    fn packs_to_rgb_u32() {
+        assert_eq!(Color::new(0x12, 0x34, 0x56).to_rgb_u32(), 0x0012_3456);
+    }
+
+    #[test]
+    fn packs_to_rgb565() {
+        assert_eq!(Color::WHITE.to_rgb565(), 0xFFFF);
+        assert_eq!(Color::BLACK.to_rgb565(), 0x0000);
+        assert_eq!(Color::new(0xFF, 0x00, 0x00).to_rgb565(), 0xF800);
+        assert_eq!(Color::new(0x00, 0xFF, 0x00).to_rgb565(), 0x07E0);
+    }
+}
@@ -0,0 +1,7 @@
+//! Shared types and utilities used across the gboxide workspace.
+
+pub mod color;
+pub mod logging;
+pub mod timing;
+
+pub use color::Color;
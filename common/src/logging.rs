@@ -0,0 +1,34 @@
+//! Minimal logging setup for the CLI and tests.
+
+use std::sync::Once;
+
+static INIT: Once = Once::new();
+
+/// Initialise the global logger once. Safe to call repeatedly.
+pub fn init() {
+    INIT.call_once(|| {
+        let _ = SimpleLogger::install();
+    });
+}
+
+struct SimpleLogger;
+
+impl SimpleLogger {
+    fn install() -> Result<(), log::SetLoggerError> {
+        log::set_logger(&SimpleLogger).map(|()| log::set_max_level(log::LevelFilter::Info))
+    }
+}
+
+impl log::Log for SimpleLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
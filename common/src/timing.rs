@@ -0,0 +1,27 @@
+//! Shared timing constants for the DMG clock and frame cadence.
+
+/// CPU clock rate in T-cycles per second.
+pub const CPU_HZ: u64 = 4_194_304;
+
+/// T-cycles per rendered frame: 456 dots × 154 lines (~59.73 Hz).
+pub const CYCLES_PER_FRAME: u64 = 70_224;
+
+/// Split an elapsed cycle count into whole frames and leftover cycles, for
+/// syncing external tools to the frame cadence.
+#[must_use]
+pub const fn cycles_to_frames(cycles: u64) -> (u64, u64) {
+    (cycles / CYCLES_PER_FRAME, cycles % CYCLES_PER_FRAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_split_into_frames_and_remainder() {
+        assert_eq!(cycles_to_frames(0), (0, 0));
+        assert_eq!(cycles_to_frames(70_224), (1, 0));
+        assert_eq!(cycles_to_frames(105_336), (1, 35_112));
+        assert_eq!(cycles_to_frames(CPU_HZ), (59, 51_088));
+    }
+}
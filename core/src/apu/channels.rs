@@ -0,0 +1,470 @@
+//! APU channel state machines: the square channels, the wave channel and
+//! the LFSR noise channel.
+
+/// Duty waveforms (12.5/25/50/75%), one bit per eighth of the period.
+const DUTY: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+/// Volume envelope shared by the square and noise channels (NRx2).
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Envelope {
+    initial: u8,
+    add: bool,
+    period: u8,
+    timer: u8,
+    pub volume: u8,
+}
+
+impl Envelope {
+    pub fn write(&mut self, value: u8) {
+        self.initial = value >> 4;
+        self.add = value & 0x08 != 0;
+        self.period = value & 0x07;
+    }
+
+    /// DAC power follows the top five bits of NRx2.
+    pub fn dac_enabled(&self) -> bool {
+        self.initial > 0 || self.add
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.initial;
+        self.timer = self.period;
+    }
+
+    /// Frame-sequencer step 7.
+    pub fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.add && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.add && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+/// Outcome of one sweep clock.
+pub(super) enum SweepTick {
+    Idle,
+    /// Write this frequency back to the channel.
+    Freq(u16),
+    /// The calculation overflowed; the channel switches off.
+    Overflow,
+}
+
+/// Channel 1 frequency sweep (NR10).
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    enabled: bool,
+    shadow: u16,
+    /// A calculation has run in negate mode since the last trigger;
+    /// clearing the negate bit afterwards kills the channel (hardware
+    /// quirk, covered by mooneye's `channel_1_sweep`).
+    negate_used: bool,
+}
+
+impl Sweep {
+    /// NR10 write. Returns `false` when the negate-mode quirk switches
+    /// the channel off.
+    pub fn write(&mut self, value: u8) -> bool {
+        let was_negate = self.negate;
+        self.period = (value >> 4) & 0x07;
+        self.negate = value & 0x08 != 0;
+        self.shift = value & 0x07;
+        !(was_negate && !self.negate && self.negate_used)
+    }
+
+    /// One sweep calculation; remembers negate-mode use for the NR10
+    /// quirk.
+    fn next_freq(&mut self) -> u16 {
+        let delta = self.shadow >> self.shift;
+        if self.negate {
+            self.negate_used = true;
+            self.shadow.wrapping_sub(delta)
+        } else {
+            self.shadow + delta
+        }
+    }
+
+    /// Load the shadow frequency on channel trigger. Returns `false` when the
+    /// immediate overflow check already kills the channel.
+    pub fn trigger(&mut self, freq: u16) -> bool {
+        self.shadow = freq;
+        self.timer = if self.period == 0 { 8 } else { self.period };
+        self.enabled = self.period != 0 || self.shift != 0;
+        self.negate_used = false;
+        self.shift == 0 || self.next_freq() <= 2047
+    }
+
+    /// Frame-sequencer steps 2 and 6.
+    pub fn clock(&mut self) -> SweepTick {
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer != 0 {
+            return SweepTick::Idle;
+        }
+        self.timer = if self.period == 0 { 8 } else { self.period };
+        if !self.enabled || self.period == 0 {
+            return SweepTick::Idle;
+        }
+        let next = self.next_freq();
+        if next > 2047 {
+            return SweepTick::Overflow;
+        }
+        if self.shift == 0 {
+            return SweepTick::Idle;
+        }
+        self.shadow = next;
+        // Second overflow check, on the frequency one step further on.
+        if self.next_freq() > 2047 {
+            return SweepTick::Overflow;
+        }
+        SweepTick::Freq(next)
+    }
+}
+
+/// Duty/length/envelope core shared by channels 1 and 2.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Square {
+    pub enabled: bool,
+    duty: u8,
+    duty_pos: u8,
+    pub freq: u16,
+    timer: usize,
+    length: u16,
+    length_enabled: bool,
+    pub envelope: Envelope,
+}
+
+impl Square {
+    pub fn write_duty_length(&mut self, value: u8) {
+        self.duty = value >> 6;
+        self.length = 64 - u16::from(value & 0x3F);
+    }
+
+    pub fn write_envelope(&mut self, value: u8) {
+        self.envelope.write(value);
+        if !self.envelope.dac_enabled() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn write_freq_low(&mut self, value: u8) {
+        self.freq = (self.freq & 0x0700) | u16::from(value);
+    }
+
+    /// NRx4 minus the trigger bit; the caller decides what trigger does.
+    pub fn write_freq_high(&mut self, value: u8) {
+        self.freq = (self.freq & 0x00FF) | (u16::from(value & 0x07) << 8);
+        self.length_enabled = value & 0x40 != 0;
+    }
+
+    pub fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        if self.length == 0 {
+            self.length = 64;
+        }
+        self.timer = self.period();
+        self.envelope.trigger();
+    }
+
+    /// T-cycles per eighth of the waveform.
+    fn period(&self) -> usize {
+        (2048 - self.freq as usize) * 4
+    }
+
+    pub fn step(&mut self, mut cycles: usize) {
+        if self.timer == 0 {
+            self.timer = self.period();
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.duty_pos = (self.duty_pos + 1) % 8;
+        }
+        self.timer -= cycles;
+    }
+
+    /// Frame-sequencer steps 0, 2, 4 and 6.
+    pub fn clock_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    /// Current DAC output, in -1.0..=1.0; 0.0 when the DAC is off.
+    pub fn output(&self) -> f32 {
+        if !self.enabled || !self.envelope.dac_enabled() {
+            return 0.0;
+        }
+        let high = (DUTY[self.duty as usize] >> self.duty_pos) & 1;
+        f32::from(high * self.envelope.volume) / 7.5 - 1.0
+    }
+}
+
+/// Channel 1: square with frequency sweep.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Channel1 {
+    pub square: Square,
+    pub sweep: Sweep,
+}
+
+impl Channel1 {
+    pub fn write_sweep(&mut self, value: u8) {
+        if !self.sweep.write(value) {
+            self.square.enabled = false;
+        }
+    }
+
+    pub fn trigger(&mut self) {
+        self.square.trigger();
+        if !self.sweep.trigger(self.square.freq) {
+            self.square.enabled = false;
+        }
+    }
+
+    /// Frame-sequencer steps 2 and 6.
+    pub fn clock_sweep(&mut self) {
+        match self.sweep.clock() {
+            SweepTick::Idle => {}
+            SweepTick::Freq(freq) => self.square.freq = freq,
+            SweepTick::Overflow => self.square.enabled = false,
+        }
+    }
+}
+
+/// Channel 3: wave RAM playback. A position counter clocked by the
+/// frequency timer walks the 32 packed nibbles, high nibble of each byte
+/// first; NR32's volume code shifts the nibble down (mute/100/50/25%).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Channel3 {
+    pub enabled: bool,
+    pub dac_enabled: bool,
+    pub wave_ram: [u8; 16],
+    length: u16,
+    length_enabled: bool,
+    pub freq: u16,
+    /// NR32 bits 5–6.
+    pub volume_code: u8,
+    /// Index of the nibble being played, 0–31.
+    position: u8,
+    timer: usize,
+}
+
+impl Channel3 {
+    pub fn write_dac(&mut self, value: u8) {
+        self.dac_enabled = value & 0x80 != 0;
+        if !self.dac_enabled {
+            self.enabled = false;
+        }
+    }
+
+    pub fn write_length(&mut self, value: u8) {
+        self.length = 256 - u16::from(value);
+    }
+
+    pub fn write_volume(&mut self, value: u8) {
+        self.volume_code = (value >> 5) & 0x03;
+    }
+
+    pub fn write_freq_low(&mut self, value: u8) {
+        self.freq = (self.freq & 0x0700) | u16::from(value);
+    }
+
+    pub fn write_freq_high(&mut self, value: u8) {
+        self.freq = (self.freq & 0x00FF) | (u16::from(value & 0x07) << 8);
+        self.length_enabled = value & 0x40 != 0;
+        if value & 0x80 != 0 {
+            self.trigger();
+        }
+    }
+
+    pub fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        if self.length == 0 {
+            self.length = 256;
+        }
+        self.position = 0;
+        self.timer = self.period();
+    }
+
+    /// T-cycles per wave-RAM nibble: twice the square channels' rate.
+    fn period(&self) -> usize {
+        (2048 - self.freq as usize) * 2
+    }
+
+    pub fn step(&mut self, mut cycles: usize) {
+        if !self.enabled {
+            return;
+        }
+        if self.timer == 0 {
+            self.timer = self.period();
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.position = (self.position + 1) % 32;
+        }
+        self.timer -= cycles;
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    /// Current DAC output, in -1.0..=1.0; 0.0 when the DAC is off.
+    pub fn output(&self) -> f32 {
+        if !self.enabled || !self.dac_enabled {
+            return 0.0;
+        }
+        let byte = self.wave_ram[(self.position / 2) as usize];
+        let nibble = if self.position % 2 == 0 {
+            byte >> 4
+        } else {
+            byte & 0x0F
+        };
+        // NR32: 0 = mute, 1 = 100%, 2 = 50%, 3 = 25%.
+        let shift = match self.volume_code {
+            0 => 4,
+            1 => 0,
+            2 => 1,
+            _ => 2,
+        };
+        f32::from(nibble >> shift) / 7.5 - 1.0
+    }
+}
+
+/// Channel 4: LFSR noise.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Channel4 {
+    pub enabled: bool,
+    length: u16,
+    length_enabled: bool,
+    pub envelope: Envelope,
+    shift: u8,
+    /// 7-bit LFSR mode (NR43 bit 3).
+    width7: bool,
+    divisor_code: u8,
+    lfsr: u16,
+    timer: usize,
+}
+
+impl Default for Channel4 {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: 0,
+            length_enabled: false,
+            envelope: Envelope::default(),
+            shift: 0,
+            width7: false,
+            divisor_code: 0,
+            lfsr: 0x7FFF,
+            timer: 0,
+        }
+    }
+}
+
+impl Channel4 {
+    pub fn write_length(&mut self, value: u8) {
+        self.length = 64 - u16::from(value & 0x3F);
+    }
+
+    pub fn write_envelope(&mut self, value: u8) {
+        self.envelope.write(value);
+        if !self.envelope.dac_enabled() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn write_poly(&mut self, value: u8) {
+        self.shift = value >> 4;
+        self.width7 = value & 0x08 != 0;
+        self.divisor_code = value & 0x07;
+    }
+
+    pub fn write_control(&mut self, value: u8) {
+        self.length_enabled = value & 0x40 != 0;
+        if value & 0x80 != 0 {
+            self.trigger();
+        }
+    }
+
+    pub fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        if self.length == 0 {
+            self.length = 64;
+        }
+        self.lfsr = 0x7FFF;
+        self.timer = self.period();
+        self.envelope.trigger();
+    }
+
+    fn period(&self) -> usize {
+        let divisor = if self.divisor_code == 0 {
+            8
+        } else {
+            self.divisor_code as usize * 16
+        };
+        divisor << self.shift
+    }
+
+    pub fn step(&mut self, mut cycles: usize) {
+        if self.timer == 0 {
+            self.timer = self.period();
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            let bit = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 14);
+            if self.width7 {
+                self.lfsr = (self.lfsr & !0x40) | (bit << 6);
+            }
+        }
+        self.timer -= cycles;
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    pub fn output(&self) -> f32 {
+        if !self.enabled || !self.envelope.dac_enabled() {
+            return 0.0;
+        }
+        let high = (!self.lfsr & 1) as u8;
+        f32::from(high * self.envelope.volume) / 7.5 - 1.0
+    }
+}
@@ -0,0 +1,822 @@
+//! Audio processing unit: the four channels, frame sequencer and stereo
+//! mixer. All four channels synthesise: squares with sweep and envelope,
+//! wave-RAM playback and LFSR noise.
+
+mod channels;
+
+use channels::{Channel1, Channel3, Channel4, Square};
+
+/// CPU clock in T-cycles per second.
+const CPU_HZ: usize = 4_194_304;
+
+/// Sample rate the host side drains at.
+pub const HOST_SAMPLE_RATE: usize = 48_000;
+
+/// Native output rate: one stereo sample every 4 T-cycles.
+pub const NATIVE_SAMPLE_RATE: usize = CPU_HZ / 4;
+
+/// Cap on buffered native-rate samples (~1/8 s); producers that never drain
+/// stop accumulating here instead of growing without bound.
+const OUTPUT_BUFFER_CAP: usize = NATIVE_SAMPLE_RATE / 8;
+
+/// Where produced samples go; the host audio backend implements this.
+pub trait AudioSink: std::fmt::Debug {
+    /// Offer one sample. Returns false when the sink is full (overrun).
+    fn push_sample(&mut self, sample: f32) -> bool;
+}
+
+/// Per-side high-pass filter modelling the DMG's output capacitors: a
+/// constant DAC level charges the capacitor and decays toward zero, so
+/// the mix carries no DC offset and channel enables do not pop. The
+/// charge factor is the conventional 0.999958 per T-cycle, raised to the
+/// cycles elapsed between samples so any output rate decays alike.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct HighPass {
+    cap_left: f32,
+    cap_right: f32,
+}
+
+impl HighPass {
+    fn filter(&mut self, (left, right): (f32, f32), cycles: usize) -> (f32, f32) {
+        let charge = 0.999_958_f32.powi(cycles as i32);
+        let out_left = left - self.cap_left;
+        let out_right = right - self.cap_right;
+        self.cap_left = left - out_left * charge;
+        self.cap_right = right - out_right * charge;
+        // A charged capacitor can briefly push an edge past full scale;
+        // keep the documented [-1.0, 1.0] output range.
+        (out_left.clamp(-1.0, 1.0), out_right.clamp(-1.0, 1.0))
+    }
+}
+
+/// APU register block (0xFF10–0xFF3F), channels and frame sequencer.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Apu {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_byte_array"))]
+    regs: [u8; 0x30],
+    ch1: Channel1,
+    ch2: Square,
+    ch3: Channel3,
+    ch4: Channel4,
+    frame_seq: u8,
+    /// Divider value at the last [`Apu::sync_div`]; the frame sequencer
+    /// clocks on falling edges of bit 12 (512 Hz).
+    last_div: u16,
+    /// CGB double speed: the sequencer watches DIV bit 13 instead of 12.
+    double_speed: bool,
+    enabled: bool,
+    /// Emulation speed multiplier, mirrored from `System::set_speed`.
+    speed: u32,
+    /// When on, fast-forward keeps the host sample rate constant by
+    /// consuming `speed` times as many emulated cycles per host sample.
+    turbo: bool,
+    sample_cycles: usize,
+    samples_produced: usize,
+    /// T-cycles accumulated toward the next native-rate output sample.
+    native_cycles: usize,
+    /// Mixed stereo samples at [`NATIVE_SAMPLE_RATE`], drained by the host.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    output_buffer: Vec<(f32, f32)>,
+    /// Host audio backend, if one is attached. Host-side, so save states
+    /// skip it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    sink: Option<Box<dyn AudioSink>>,
+    /// Push silence to the sink instead of the mix (e.g. while the window
+    /// is unfocused). Host-side, so save states skip it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    muted: bool,
+    /// High-pass state for the native-rate buffer, charged 4 T-cycles per
+    /// sample.
+    native_filter: HighPass,
+    /// High-pass state for the host-rate sink path, charged per host
+    /// sample period.
+    host_filter: HighPass,
+    /// Samples the sink refused because it was full.
+    overruns: u64,
+    /// Silent samples the host had to emit because we ran dry.
+    underruns: u64,
+}
+
+impl Clone for Apu {
+    /// Clones everything except the attached host sink, which is not
+    /// duplicable; the clone starts with no sink.
+    fn clone(&self) -> Self {
+        Self {
+            regs: self.regs,
+            ch1: self.ch1.clone(),
+            ch2: self.ch2.clone(),
+            ch3: self.ch3.clone(),
+            ch4: self.ch4.clone(),
+            frame_seq: self.frame_seq,
+            last_div: self.last_div,
+            double_speed: self.double_speed,
+            enabled: self.enabled,
+            speed: self.speed,
+            turbo: self.turbo,
+            sample_cycles: self.sample_cycles,
+            samples_produced: self.samples_produced,
+            native_cycles: self.native_cycles,
+            output_buffer: self.output_buffer.clone(),
+            sink: None,
+            muted: self.muted,
+            native_filter: self.native_filter,
+            host_filter: self.host_filter,
+            overruns: self.overruns,
+            underruns: self.underruns,
+        }
+    }
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self {
+            regs: [0; 0x30],
+            ch1: Channel1::default(),
+            ch2: Square::default(),
+            ch3: Channel3::default(),
+            ch4: Channel4::default(),
+            frame_seq: 0,
+            last_div: 0,
+            double_speed: false,
+            enabled: true,
+            speed: 1,
+            turbo: false,
+            sample_cycles: 0,
+            samples_produced: 0,
+            native_cycles: 0,
+            output_buffer: Vec::new(),
+            sink: None,
+            muted: false,
+            native_filter: HighPass::default(),
+            host_filter: HighPass::default(),
+            overruns: 0,
+            underruns: 0,
+        }
+    }
+}
+
+impl Apu {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn read_reg(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF26 => {
+                (u8::from(self.enabled) << 7)
+                    | 0x70
+                    | u8::from(self.ch1.square.enabled)
+                    | (u8::from(self.ch2.enabled) << 1)
+                    | (u8::from(self.ch3.enabled) << 2)
+                    | (u8::from(self.ch4.enabled) << 3)
+            }
+            0xFF30..=0xFF3F => self.ch3.wave_ram[(addr - 0xFF30) as usize],
+            0xFF10..=0xFF2F => self.regs[(addr - 0xFF10) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_reg(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF26 => {
+                self.enabled = value & 0x80 != 0;
+                if !self.enabled {
+                    self.power_off();
+                }
+                return;
+            }
+            0xFF30..=0xFF3F => {
+                // Wave RAM stays writable while the APU is powered off.
+                self.ch3.wave_ram[(addr - 0xFF30) as usize] = value;
+                return;
+            }
+            0xFF10..=0xFF2F if !self.enabled => return,
+            0xFF10..=0xFF2F => self.regs[(addr - 0xFF10) as usize] = value,
+            _ => return,
+        }
+        match addr {
+            0xFF10 => self.ch1.write_sweep(value),
+            0xFF11 => self.ch1.square.write_duty_length(value),
+            0xFF12 => self.ch1.square.write_envelope(value),
+            0xFF13 => self.ch1.square.write_freq_low(value),
+            0xFF14 => {
+                self.ch1.square.write_freq_high(value);
+                if value & 0x80 != 0 {
+                    self.ch1.trigger();
+                }
+            }
+            0xFF16 => self.ch2.write_duty_length(value),
+            0xFF17 => self.ch2.write_envelope(value),
+            0xFF18 => self.ch2.write_freq_low(value),
+            0xFF19 => {
+                self.ch2.write_freq_high(value);
+                if value & 0x80 != 0 {
+                    self.ch2.trigger();
+                }
+            }
+            0xFF1A => self.ch3.write_dac(value),
+            0xFF1B => self.ch3.write_length(value),
+            0xFF1C => self.ch3.write_volume(value),
+            0xFF1D => self.ch3.write_freq_low(value),
+            0xFF1E => self.ch3.write_freq_high(value),
+            0xFF20 => self.ch4.write_length(value),
+            0xFF21 => self.ch4.write_envelope(value),
+            0xFF22 => self.ch4.write_poly(value),
+            0xFF23 => self.ch4.write_control(value),
+            _ => {}
+        }
+    }
+
+    /// APU power-off: NR10–NR51 read back as zero and all channel state is
+    /// dropped. Wave RAM survives, as on hardware.
+    fn power_off(&mut self) {
+        self.regs[..0x16].fill(0);
+        let wave_ram = self.ch3.wave_ram;
+        self.ch1 = Channel1::default();
+        self.ch2 = Square::default();
+        self.ch3 = Channel3::default();
+        self.ch3.wave_ram = wave_ram;
+        self.ch4 = Channel4::default();
+    }
+
+    /// Enable turbo resampling for fast-forward. Without turbo, running at
+    /// `speed` > 1 produces `speed` times the samples (chipmunk pitch when
+    /// drained at the host rate).
+    pub fn set_turbo(&mut self, on: bool) {
+        self.turbo = on;
+    }
+
+    #[must_use]
+    pub fn turbo(&self) -> bool {
+        self.turbo
+    }
+
+    /// Mirror of the system speed multiplier.
+    pub fn set_speed(&mut self, multiplier: u32) {
+        self.speed = multiplier.max(1);
+    }
+
+    /// Host-rate samples produced so far; tracks sink pacing, not the
+    /// native-rate output buffer.
+    #[must_use]
+    pub fn samples_produced(&self) -> usize {
+        self.samples_produced
+    }
+
+    /// Silence the sink without detaching it: pacing continues, so audio
+    /// resumes seamlessly when unmuted.
+    pub fn set_muted(&mut self, on: bool) {
+        self.muted = on;
+    }
+
+    #[must_use]
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Attach the host audio backend that produced samples are pushed into.
+    pub fn set_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.sink = Some(sink);
+    }
+
+    /// Samples the sink refused because it was full.
+    #[must_use]
+    pub fn overruns(&self) -> u64 {
+        self.overruns
+    }
+
+    /// Silent samples the host reported emitting because we ran dry.
+    #[must_use]
+    pub fn underruns(&self) -> u64 {
+        self.underruns
+    }
+
+    /// Called by the host backend when it drained an empty buffer and had to
+    /// emit `count` samples of silence.
+    pub fn report_underrun(&mut self, count: u64) {
+        self.underruns += count;
+        tracing::trace!(
+            target: "gboxide::apu",
+            count,
+            total = self.underruns,
+            "audio underrun: emitted silence"
+        );
+    }
+
+    /// Wave RAM unpacked into its 32 4-bit samples, high nibble of each
+    /// byte first — the order the channel plays them in.
+    #[must_use]
+    pub fn wave_samples(&self) -> [u8; 32] {
+        let mut samples = [0; 32];
+        for (index, byte) in self.ch3.wave_ram.iter().enumerate() {
+            samples[index * 2] = byte >> 4;
+            samples[index * 2 + 1] = byte & 0x0F;
+        }
+        samples
+    }
+
+    /// Current mixed stereo output. The formula, fixed so the result is
+    /// deterministic regardless of summation concerns: sum the DAC outputs
+    /// (each -1.0..=1.0) of the channels NR51 routes to a side in channel
+    /// order 1–4, scale by 1/4 and by the NR50 volume ((v+1)/8), then clamp
+    /// to [-1.0, 1.0].
+    #[must_use]
+    pub fn mix_sample(&self) -> (f32, f32) {
+        let nr50 = self.regs[0x14];
+        let nr51 = self.regs[0x15];
+        let outputs = [
+            self.ch1.square.output(),
+            self.ch2.output(),
+            self.ch3.output(),
+            self.ch4.output(),
+        ];
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for (channel, output) in outputs.iter().enumerate() {
+            if nr51 & (0x10 << channel) != 0 {
+                left += output;
+            }
+            if nr51 & (0x01 << channel) != 0 {
+                right += output;
+            }
+        }
+        let left_volume = f32::from(((nr50 >> 4) & 0x07) + 1) / 8.0;
+        let right_volume = f32::from((nr50 & 0x07) + 1) / 8.0;
+        (
+            (left / 4.0 * left_volume).clamp(-1.0, 1.0),
+            (right / 4.0 * right_volume).clamp(-1.0, 1.0),
+        )
+    }
+
+    /// Take the buffered native-rate output, linearly resampled down to
+    /// `sample_rate`.
+    pub fn drain(&mut self, sample_rate: usize) -> Vec<(f32, f32)> {
+        let source = std::mem::take(&mut self.output_buffer);
+        if source.is_empty() || sample_rate == 0 {
+            return Vec::new();
+        }
+        let out_len = source.len() * sample_rate / NATIVE_SAMPLE_RATE;
+        let step = NATIVE_SAMPLE_RATE as f64 / sample_rate as f64;
+        let mut out = Vec::with_capacity(out_len);
+        for index in 0..out_len {
+            let pos = index as f64 * step;
+            let base = pos as usize;
+            let frac = (pos - base as f64) as f32;
+            let a = source[base];
+            let b = source[(base + 1).min(source.len() - 1)];
+            out.push((a.0 + (b.0 - a.0) * frac, a.1 + (b.1 - a.1) * frac));
+        }
+        out
+    }
+
+    /// Emulated cycles consumed per host-rate output sample.
+    fn cycles_per_host_sample(&self) -> usize {
+        let base = CPU_HZ / HOST_SAMPLE_RATE;
+        if self.turbo {
+            base * self.speed as usize
+        } else {
+            base
+        }
+    }
+
+    /// Report the timer's internal divider. The frame sequencer clocks on
+    /// falling edges of DIV bit 12, like hardware — which also reproduces
+    /// the DIV-APU quirk where resetting DIV with the bit high produces an
+    /// extra sequencer clock. In CGB double speed the divider ticks twice
+    /// as fast, so the sequencer watches bit 13 to stay at 512 Hz.
+    pub fn sync_div(&mut self, div: u16) {
+        let mask = if self.double_speed { 0x2000 } else { 0x1000 };
+        let fell = self.last_div & mask != 0 && div & mask == 0;
+        self.last_div = div;
+        if fell && self.enabled {
+            self.clock_frame_sequencer();
+        }
+    }
+
+    /// Switch the CGB double-speed mode on or off; see [`Apu::sync_div`].
+    pub fn set_double_speed(&mut self, on: bool) {
+        self.double_speed = on;
+    }
+
+    /// One 512 Hz frame-sequencer step: lengths on even steps, sweep on 2
+    /// and 6, envelopes on 7.
+    fn clock_frame_sequencer(&mut self) {
+        self.frame_seq = (self.frame_seq + 1) % 8;
+        if self.frame_seq % 2 == 0 {
+            self.ch1.square.clock_length();
+            self.ch2.clock_length();
+            self.ch3.clock_length();
+            self.ch4.clock_length();
+        }
+        if self.frame_seq == 2 || self.frame_seq == 6 {
+            self.ch1.clock_sweep();
+        }
+        if self.frame_seq == 7 {
+            self.ch1.square.envelope.clock();
+            self.ch2.envelope.clock();
+            self.ch4.envelope.clock();
+        }
+    }
+
+    /// Advance the channels and output pacing by `cycles` T-cycles. The
+    /// frame sequencer is driven separately via [`Apu::sync_div`].
+    pub fn step(&mut self, cycles: usize) {
+        if !self.enabled {
+            return;
+        }
+        self.ch1.square.step(cycles);
+        self.ch2.step(cycles);
+        self.ch3.step(cycles);
+        self.ch4.step(cycles);
+
+        self.native_cycles += cycles;
+        while self.native_cycles >= 4 {
+            self.native_cycles -= 4;
+            // Filter even when the buffer is full so the capacitor charge
+            // keeps tracking emulated time.
+            let sample = self.native_filter.filter(self.mix_sample(), 4);
+            if self.output_buffer.len() < OUTPUT_BUFFER_CAP {
+                self.output_buffer.push(sample);
+            }
+        }
+
+        self.sample_cycles += cycles;
+        let period = self.cycles_per_host_sample();
+        while self.sample_cycles >= period {
+            self.sample_cycles -= period;
+            self.samples_produced += 1;
+            let (left, right) = self.host_filter.filter(self.mix_sample(), period);
+            let mono = if self.muted {
+                0.0
+            } else {
+                (left + right) * 0.5
+            };
+            if let Some(sink) = &mut self.sink {
+                if !sink.push_sample(mono) {
+                    self.overruns += 1;
+                    tracing::trace!(
+                        target: "gboxide::apu",
+                        total = self.overruns,
+                        "audio overrun: sink refused a sample"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turbo_keeps_host_sample_count_constant_at_2x() {
+        let one_second = 4_194_304;
+
+        let mut plain = Apu::new();
+        plain.set_speed(2);
+        plain.step(one_second * 2); // 2x speed: twice the emulated cycles
+        assert!(plain.samples_produced() >= HOST_SAMPLE_RATE * 2 - 1);
+
+        let mut turbo = Apu::new();
+        turbo.set_speed(2);
+        turbo.set_turbo(true);
+        turbo.step(one_second * 2);
+        let produced = turbo.samples_produced();
+        // The cycle period truncates, so allow 1% drift from the host rate.
+        let tolerance = HOST_SAMPLE_RATE / 100;
+        assert!(
+            produced.abs_diff(HOST_SAMPLE_RATE) <= tolerance,
+            "expected ~{HOST_SAMPLE_RATE} samples, got {produced}"
+        );
+        assert_eq!(produced, plain.samples_produced() / 2);
+    }
+
+    /// Sink that accepts a fixed number of samples, then refuses everything.
+    #[derive(Debug)]
+    struct TinySink {
+        capacity: usize,
+        held: usize,
+    }
+
+    impl AudioSink for TinySink {
+        fn push_sample(&mut self, _sample: f32) -> bool {
+            if self.held < self.capacity {
+                self.held += 1;
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    #[test]
+    fn producing_faster_than_the_sink_drains_counts_overruns() {
+        let mut apu = Apu::new();
+        apu.set_sink(Box::new(TinySink {
+            capacity: 4,
+            held: 0,
+        }));
+        apu.step(4_194_304); // one emulated second, never drained
+        assert_eq!(
+            apu.overruns(),
+            (apu.samples_produced() - 4) as u64,
+            "everything past the sink capacity is an overrun"
+        );
+
+        apu.report_underrun(3);
+        assert_eq!(apu.underruns(), 3);
+    }
+
+    #[test]
+    fn nr52_reflects_power_state() {
+        let mut apu = Apu::new();
+        assert_eq!(apu.read_reg(0xFF26) & 0x80, 0x80);
+        apu.write_reg(0xFF26, 0x00);
+        assert_eq!(apu.read_reg(0xFF26) & 0x80, 0x00);
+    }
+
+    /// Trigger channel 1 as a square wave and return the left-channel duty
+    /// ratio over several waveform periods.
+    fn square_duty_ratio(duty_bits: u8) -> f32 {
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF24, 0x77); // full master volume both sides
+        apu.write_reg(0xFF25, 0x11); // channel 1 to both sides
+        apu.write_reg(0xFF12, 0xF0); // volume 15, no envelope
+        apu.write_reg(0xFF11, duty_bits << 6);
+        apu.write_reg(0xFF13, 0x00);
+        apu.write_reg(0xFF14, 0x87); // trigger, frequency 0x700
+
+        // Period (2048-1792)*4 = 1024 cycles per duty step; 64 K cycles
+        // covers eight full waveform periods. Step at instruction
+        // granularity so the buffer samples the waveform as it moves.
+        for _ in 0..16_384 {
+            apu.step(4);
+        }
+        let samples = apu.drain(NATIVE_SAMPLE_RATE);
+        assert!(!samples.is_empty());
+        let high = samples.iter().filter(|(left, _)| *left > 0.0).count();
+        high as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn triggered_square_channel_follows_its_duty_cycle() {
+        assert!((square_duty_ratio(0b10) - 0.5).abs() < 0.02, "50% duty");
+        assert!((square_duty_ratio(0b00) - 0.125).abs() < 0.02, "12.5% duty");
+
+        // NR52 reports the channel as live.
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF12, 0xF0);
+        apu.write_reg(0xFF14, 0x80);
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01);
+    }
+
+    /// Trigger channel 1 with the given NR51 routing and collect output.
+    fn pan_samples(nr51: u8) -> Vec<(f32, f32)> {
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF24, 0x77);
+        apu.write_reg(0xFF25, nr51);
+        apu.write_reg(0xFF12, 0xF0);
+        apu.write_reg(0xFF13, 0x00);
+        apu.write_reg(0xFF14, 0x87);
+        for _ in 0..2_048 {
+            apu.step(4);
+        }
+        apu.drain(NATIVE_SAMPLE_RATE)
+    }
+
+    #[test]
+    fn mixing_at_full_blast_is_clamped_and_deterministic() {
+        // Every channel DAC on at max volume, routed everywhere, NR50 max.
+        fn loud_apu() -> Apu {
+            let mut apu = Apu::new();
+            apu.write_reg(0xFF24, 0xFF);
+            apu.write_reg(0xFF25, 0xFF);
+            for nrx2 in [0xFF12, 0xFF17, 0xFF21] {
+                apu.write_reg(nrx2, 0xF0);
+            }
+            for nrx4 in [0xFF14, 0xFF19, 0xFF23] {
+                apu.write_reg(nrx4, 0x80);
+            }
+            for _ in 0..2_048 {
+                apu.step(4);
+            }
+            apu
+        }
+
+        let mut first = loud_apu();
+        let mut second = loud_apu();
+        let a = first.drain(NATIVE_SAMPLE_RATE);
+        let b = second.drain(NATIVE_SAMPLE_RATE);
+        assert!(!a.is_empty());
+        assert!(a
+            .iter()
+            .all(|(l, r)| (-1.0..=1.0).contains(l) && (-1.0..=1.0).contains(r)));
+        assert_eq!(a, b, "identical inputs must mix bit-identically");
+    }
+
+    #[test]
+    fn nr51_panning_routes_channels_per_side() {
+        // Hard left: the right side must stay silent.
+        let hard_left = pan_samples(0x10);
+        assert!(hard_left.iter().any(|(left, _)| *left != 0.0));
+        assert!(hard_left.iter().all(|(_, right)| *right == 0.0));
+
+        // Routed to neither side: silent everywhere.
+        let unrouted = pan_samples(0x00);
+        assert!(unrouted.iter().all(|(l, r)| *l == 0.0 && *r == 0.0));
+
+        // Routed to both sides with equal NR50 volumes: centered.
+        let centered = pan_samples(0x11);
+        assert!(centered.iter().all(|(l, r)| l == r));
+    }
+
+    #[test]
+    fn wave_samples_unpacks_nibbles_high_first() {
+        let mut apu = Apu::new();
+        for (offset, value) in (0..16u8).map(|i| (i, i * 0x11)) {
+            apu.write_reg(0xFF30 + u16::from(offset), value);
+        }
+        let samples = apu.wave_samples();
+        for (index, sample) in samples.iter().enumerate() {
+            assert_eq!(usize::from(*sample), index / 2);
+        }
+
+        apu.write_reg(0xFF30, 0x8F);
+        assert_eq!(apu.wave_samples()[0], 0x8);
+        assert_eq!(apu.wave_samples()[1], 0xF);
+    }
+
+    #[test]
+    fn clearing_negate_after_a_negate_mode_calculation_disables_channel_1() {
+        // Mooneye `channel_1_sweep`: once any sweep calculation has run in
+        // negate mode, clearing NR10's negate bit kills the channel.
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF12, 0xF0); // DAC on
+        apu.write_reg(0xFF10, 0x19); // period 1, negate, shift 1
+        apu.write_reg(0xFF14, 0x80); // trigger runs the check in negate mode
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01);
+
+        apu.write_reg(0xFF10, 0x11); // same sweep, negate cleared
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x00, "died on the write");
+
+        // With shift 0 the trigger computes nothing, so clearing negate
+        // is harmless until a sweep clock has actually used it.
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF12, 0xF0);
+        apu.write_reg(0xFF10, 0x08); // negate, no period, no shift
+        apu.write_reg(0xFF14, 0x80);
+        apu.write_reg(0xFF10, 0x00);
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01, "no calculation yet");
+    }
+
+    #[test]
+    fn the_high_pass_filter_decays_a_constant_dac_level_toward_zero() {
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF24, 0x77);
+        apu.write_reg(0xFF25, 0x44); // channel 3 to both sides
+        for addr in 0xFF30..=0xFF3F {
+            apu.write_reg(addr, 0xFF); // every sample 15: a constant DAC level
+        }
+        apu.write_reg(0xFF1A, 0x80); // DAC on
+        apu.write_reg(0xFF1C, 0x20); // 100%
+        apu.write_reg(0xFF1E, 0x80); // trigger
+
+        // 256 K cycles is about eleven capacitor time constants.
+        for _ in 0..65_536 {
+            apu.step(4);
+        }
+        let samples = apu.drain(NATIVE_SAMPLE_RATE);
+        let first = samples.first().unwrap().0;
+        let last = samples.last().unwrap().0;
+        assert!(first > 0.2, "the level passes through at first: {first}");
+        assert!(last.abs() < 1e-3, "the offset has decayed away: {last}");
+        assert!(
+            samples.windows(2).all(|pair| pair[1].0 <= pair[0].0),
+            "a constant input decays monotonically"
+        );
+    }
+
+    #[test]
+    fn wave_channel_steps_through_wave_ram_at_the_channel_frequency() {
+        let mut apu = Apu::new();
+        // Ramp: byte n holds nibble n twice, so positions 2n and 2n+1
+        // both play sample n.
+        for (index, byte) in apu.ch3.wave_ram.iter_mut().enumerate() {
+            *byte = ((index as u8) << 4) | index as u8;
+        }
+        apu.write_reg(0xFF1A, 0x80); // DAC on
+        apu.write_reg(0xFF1C, 0x20); // NR32 code 1: 100%
+        apu.write_reg(0xFF1D, 0xFE);
+        apu.write_reg(0xFF1E, 0x87); // trigger, frequency 2046: 4 cycles/nibble
+
+        for position in 0..64u32 {
+            let nibble = (position % 32) / 2;
+            let expected = nibble as f32 / 7.5 - 1.0;
+            assert!(
+                (apu.ch3.output() - expected).abs() < 1e-6,
+                "position {position}: expected sample {nibble}"
+            );
+            apu.step(4);
+        }
+
+        // Position 6 plays nibble 3; NR32 code 2 shifts it down to 50%.
+        for _ in 0..6 {
+            apu.step(4);
+        }
+        apu.write_reg(0xFF1C, 0x40);
+        assert!((apu.ch3.output() - (1.0 / 7.5 - 1.0)).abs() < 1e-6);
+
+        // Disabling the DAC silences the channel outright.
+        apu.write_reg(0xFF1A, 0x00);
+        assert_eq!(apu.ch3.output(), 0.0);
+    }
+
+    #[test]
+    fn div_bit_12_falling_edges_clock_the_frame_sequencer() {
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF12, 0xF0);
+        apu.write_reg(0xFF11, 0x3F); // length counter = 1
+        apu.write_reg(0xFF14, 0xC0); // trigger with length enable
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01);
+
+        // The DIV-APU quirk: each DIV reset with bit 12 high is an extra
+        // sequencer clock. Two clocks reach an even (length) step.
+        apu.sync_div(0x1000);
+        apu.sync_div(0x0000);
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01, "odd step: no length");
+        apu.sync_div(0x1000);
+        apu.sync_div(0x0000);
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x00, "length expired");
+    }
+
+    #[test]
+    fn double_speed_moves_the_frame_sequencer_to_div_bit_13() {
+        let mut apu = Apu::new();
+        apu.write_reg(0xFF12, 0xF0);
+        apu.write_reg(0xFF11, 0x3F); // length counter = 1
+        apu.write_reg(0xFF14, 0xC0); // trigger with length enable
+        apu.set_double_speed(true);
+
+        // Bit 12 falls at twice the wall-clock rate now; ignoring it keeps
+        // the sequencer at 512 Hz.
+        for _ in 0..2 {
+            apu.sync_div(0x1000);
+            apu.sync_div(0x0000);
+        }
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x01, "bit 12 no longer clocks");
+
+        for _ in 0..2 {
+            apu.sync_div(0x2000);
+            apu.sync_div(0x0000);
+        }
+        assert_eq!(apu.read_reg(0xFF26) & 0x01, 0x00, "bit 13 does");
+    }
+
+    #[test]
+    fn nr52_status_bits_track_channel_triggers() {
+        let mut apu = Apu::new();
+        assert_eq!(apu.read_reg(0xFF26) & 0x0F, 0x00);
+
+        apu.write_reg(0xFF17, 0xF0); // channel 2 DAC on
+        apu.write_reg(0xFF19, 0x80); // trigger
+        assert_eq!(apu.read_reg(0xFF26) & 0x0F, 0x02);
+
+        apu.write_reg(0xFF21, 0xF0); // channel 4 DAC on
+        apu.write_reg(0xFF23, 0x80); // trigger
+        assert_eq!(apu.read_reg(0xFF26) & 0x0F, 0x0A);
+        assert_eq!(apu.read_reg(0xFF26) & 0x70, 0x70, "bits 4-6 read as 1");
+    }
+
+    #[test]
+    fn powering_off_zeroes_the_register_file() {
+        let mut apu = Apu::new();
+        for addr in 0xFF10..=0xFF25 {
+            apu.write_reg(addr, 0xFF);
+        }
+        apu.write_reg(0xFF30, 0x5A);
+
+        apu.write_reg(0xFF26, 0x00);
+        apu.write_reg(0xFF26, 0x80);
+        for addr in 0xFF10..=0xFF25 {
+            assert_eq!(apu.read_reg(addr), 0x00, "{addr:#06X} not cleared");
+        }
+        assert_eq!(apu.read_reg(0xFF30), 0x5A, "wave RAM survives power-off");
+    }
+
+    #[test]
+    fn drain_resamples_to_the_requested_rate() {
+        let mut apu = Apu::new();
+        apu.step(65_536); // 16384 native samples
+        let samples = apu.drain(HOST_SAMPLE_RATE);
+        assert_eq!(
+            samples.len(),
+            16_384 * HOST_SAMPLE_RATE / NATIVE_SAMPLE_RATE
+        );
+        assert!(apu.drain(HOST_SAMPLE_RATE).is_empty(), "drain consumes");
+    }
+}
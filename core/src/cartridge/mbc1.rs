@@ -0,0 +1,105 @@
+//! MBC1: up to 2 MB ROM via a 5-bit bank register plus 2 upper bits, with
+//! the 0x6000 mode select routing the upper bits to ROM or RAM banking.
+
+use super::{BankState, Mbc};
+
+pub(super) struct Mbc1 {
+    ram_enabled: bool,
+    rom_bank: u8,
+    bank_hi: u8,
+    /// 0x6000 mode select: 0 routes `bank_hi` to ROM A19–A20 only; 1 also
+    /// applies it to the 0x0000 ROM window and to RAM banking.
+    mode: u8,
+    /// RAM banking only exists on 32 KiB carts; 2/8 KiB carts have no A13+
+    /// lines to bank, so `bank_hi` must not shift their RAM addresses.
+    banked_ram: bool,
+}
+
+impl Mbc1 {
+    pub(super) fn new(ram_size: usize) -> Self {
+        Self {
+            ram_enabled: false,
+            rom_bank: 1,
+            bank_hi: 0,
+            mode: 0,
+            banked_ram: ram_size > 0x2000,
+        }
+    }
+}
+
+impl Mbc for Mbc1 {
+    fn rom_addr(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => {
+                // Mode 1 swaps the fixed window to bank (bank_hi << 5).
+                let bank = if self.mode == 1 {
+                    (self.bank_hi as usize) << 5
+                } else {
+                    0
+                };
+                bank * 0x4000 + addr as usize
+            }
+            _ => {
+                let bank = (self.bank_hi as usize) << 5 | self.rom_bank as usize;
+                bank * 0x4000 + (addr as usize - 0x4000)
+            }
+        }
+    }
+
+    fn ram_addr(&self, addr: u16) -> Option<usize> {
+        self.ram_enabled.then(|| {
+            let bank = if self.mode == 1 && self.banked_ram {
+                self.bank_hi as usize
+            } else {
+                0
+            };
+            bank * 0x2000 + (addr - 0xA000) as usize
+        })
+    }
+
+    fn write_control(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                let bank = value & 0x1F;
+                self.rom_bank = if bank == 0 { 1 } else { bank };
+            }
+            0x4000..=0x5FFF => self.bank_hi = value & 0x03,
+            0x6000..=0x7FFF => self.mode = value & 0x01,
+            _ => {}
+        }
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            rom_bank: u16::from(self.bank_hi) << 5 | u16::from(self.rom_bank),
+            ram_bank: if self.mode == 1 && self.banked_ram {
+                self.bank_hi
+            } else {
+                0
+            },
+            ram_enabled: self.ram_enabled,
+            rtc_selected: false,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_registers(&self) -> Vec<u8> {
+        vec![
+            u8::from(self.ram_enabled),
+            self.rom_bank,
+            self.bank_hi,
+            self.mode,
+        ]
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_registers(&mut self, bytes: &[u8]) {
+        if let [ram_enabled, rom_bank, bank_hi, mode] = *bytes {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = rom_bank;
+            self.bank_hi = bank_hi;
+            self.mode = mode;
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! MBC3: 7-bit ROM banking, 4 RAM banks and the real-time clock. RTC
+//! registers appear in the 0xA000 window when banks 0x08–0x0C are selected;
+//! reads see the latched copy, frozen by the 0x00→0x01 latch sequence.
+
+use super::{BankState, Mbc};
+
+/// One snapshot of the clock registers (seconds/minutes/hours/day/day-high).
+#[derive(Debug, Clone, Copy, Default)]
+struct Rtc {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    day_low: u8,
+    /// Bit 0: day counter bit 8, bit 6: halt, bit 7: day carry.
+    day_high: u8,
+}
+
+impl Rtc {
+    fn read(&self, bank: u8) -> u8 {
+        match bank {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.day_low,
+            _ => self.day_high,
+        }
+    }
+
+    fn write(&mut self, bank: u8, value: u8) {
+        match bank {
+            0x08 => self.seconds = value & 0x3F,
+            0x09 => self.minutes = value & 0x3F,
+            0x0A => self.hours = value & 0x1F,
+            0x0B => self.day_low = value,
+            _ => self.day_high = value & 0xC1,
+        }
+    }
+}
+
+pub(super) struct Mbc3 {
+    ram_enabled: bool,
+    rom_bank: u8,
+    /// 0x00–0x03 select RAM banks, 0x08–0x0C the RTC registers.
+    ram_bank: u8,
+    rtc: Rtc,
+    latched: Rtc,
+    /// A 0x00 was written to the latch area; the next 0x01 latches.
+    latch_pending: bool,
+}
+
+impl Mbc3 {
+    pub(super) fn new() -> Self {
+        Self {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rtc: Rtc::default(),
+            latched: Rtc::default(),
+            latch_pending: false,
+        }
+    }
+
+    fn rtc_selected(&self) -> bool {
+        (0x08..=0x0C).contains(&self.ram_bank)
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn rom_addr(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => addr as usize,
+            _ => self.rom_bank as usize * 0x4000 + (addr as usize - 0x4000),
+        }
+    }
+
+    fn ram_addr(&self, addr: u16) -> Option<usize> {
+        (self.ram_enabled && self.ram_bank <= 0x03)
+            .then(|| self.ram_bank as usize * 0x2000 + (addr - 0xA000) as usize)
+    }
+
+    fn write_control(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                let bank = value & 0x7F;
+                self.rom_bank = if bank == 0 { 1 } else { bank };
+            }
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            0x6000..=0x7FFF => {
+                if value == 0x01 && self.latch_pending {
+                    self.latched = self.rtc;
+                }
+                self.latch_pending = value == 0x00;
+            }
+            _ => {}
+        }
+    }
+
+    fn read_register(&self, _addr: u16) -> Option<u8> {
+        (self.ram_enabled && self.rtc_selected()).then(|| self.latched.read(self.ram_bank))
+    }
+
+    fn write_register(&mut self, _addr: u16, value: u8) -> bool {
+        if self.ram_enabled && self.rtc_selected() {
+            self.rtc.write(self.ram_bank, value);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            rom_bank: u16::from(self.rom_bank),
+            ram_bank: self.ram_bank,
+            ram_enabled: self.ram_enabled,
+            rtc_selected: self.rtc_selected(),
+        }
+    }
+
+    fn set_clock(&mut self, unix_time: u64) {
+        // Halt bit set: the clock is stopped, host time is ignored.
+        if self.rtc.day_high & 0x40 != 0 {
+            return;
+        }
+        self.rtc.seconds = (unix_time % 60) as u8;
+        self.rtc.minutes = (unix_time / 60 % 60) as u8;
+        self.rtc.hours = (unix_time / 3600 % 24) as u8;
+        let days = unix_time / 86400;
+        self.rtc.day_low = (days & 0xFF) as u8;
+        self.rtc.day_high = (self.rtc.day_high & 0xC0) | ((days >> 8) & 0x01) as u8;
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_registers(&self) -> Vec<u8> {
+        let mut bytes = vec![
+            u8::from(self.ram_enabled),
+            self.rom_bank,
+            self.ram_bank,
+            u8::from(self.latch_pending),
+        ];
+        for rtc in [&self.rtc, &self.latched] {
+            bytes.extend_from_slice(&[
+                rtc.seconds,
+                rtc.minutes,
+                rtc.hours,
+                rtc.day_low,
+                rtc.day_high,
+            ]);
+        }
+        bytes
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_registers(&mut self, bytes: &[u8]) {
+        if let [ram_enabled, rom_bank, ram_bank, latch_pending, live @ ..] = bytes {
+            self.ram_enabled = *ram_enabled != 0;
+            self.rom_bank = *rom_bank;
+            self.ram_bank = *ram_bank;
+            self.latch_pending = *latch_pending != 0;
+            if let [s0, m0, h0, dl0, dh0, s1, m1, h1, dl1, dh1] = *live {
+                self.rtc = Rtc {
+                    seconds: s0,
+                    minutes: m0,
+                    hours: h0,
+                    day_low: dl0,
+                    day_high: dh0,
+                };
+                self.latched = Rtc {
+                    seconds: s1,
+                    minutes: m1,
+                    hours: h1,
+                    day_low: dl1,
+                    day_high: dh1,
+                };
+            }
+        }
+    }
+}
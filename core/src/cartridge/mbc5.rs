@@ -0,0 +1,95 @@
+//! MBC5: up to 8 MB ROM via a 9-bit bank register, 16 RAM banks, and an
+//! optional rumble motor on bit 3 of the RAM-bank register.
+
+use super::{BankState, Mbc};
+
+pub(super) struct Mbc5 {
+    ram_enabled: bool,
+    /// 9-bit ROM bank: low 8 bits at 0x2000–0x2FFF, bit 8 at 0x3000–0x3FFF.
+    /// Unlike MBC1, bank 0 is selectable in the switchable window.
+    rom_bank: u16,
+    ram_bank: u8,
+    rumble: bool,
+    /// Rumble carts repurpose bit 3 of the RAM-bank register for the motor,
+    /// leaving 3 bits of RAM banking.
+    has_rumble: bool,
+}
+
+impl Mbc5 {
+    pub(super) fn new(has_rumble: bool) -> Self {
+        Self {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rumble: false,
+            has_rumble,
+        }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn rom_addr(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => addr as usize,
+            _ => self.rom_bank as usize * 0x4000 + (addr as usize - 0x4000),
+        }
+    }
+
+    fn ram_addr(&self, addr: u16) -> Option<usize> {
+        self.ram_enabled
+            .then(|| self.ram_bank as usize * 0x2000 + (addr - 0xA000) as usize)
+    }
+
+    fn write_control(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | u16::from(value),
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0x0FF) | (u16::from(value & 0x01) << 8);
+            }
+            0x4000..=0x5FFF => {
+                if self.has_rumble {
+                    self.rumble = value & 0x08 != 0;
+                    self.ram_bank = value & 0x07;
+                } else {
+                    self.ram_bank = value & 0x0F;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+
+    fn bank_state(&self) -> BankState {
+        BankState {
+            rom_bank: self.rom_bank,
+            ram_bank: self.ram_bank,
+            ram_enabled: self.ram_enabled,
+            rtc_selected: false,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_registers(&self) -> Vec<u8> {
+        vec![
+            u8::from(self.ram_enabled),
+            (self.rom_bank & 0xFF) as u8,
+            (self.rom_bank >> 8) as u8,
+            self.ram_bank,
+            u8::from(self.rumble),
+        ]
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_registers(&mut self, bytes: &[u8]) {
+        if let [ram_enabled, rom_lo, rom_hi, ram_bank, rumble] = *bytes {
+            self.ram_enabled = ram_enabled != 0;
+            self.rom_bank = u16::from_le_bytes([rom_lo, rom_hi]) & 0x1FF;
+            self.ram_bank = ram_bank;
+            self.rumble = rumble != 0;
+        }
+    }
+}
@@ -0,0 +1,844 @@
+//! Cartridge loading, header parsing and memory bank controllers.
+
+mod mbc1;
+mod mbc3;
+mod mbc5;
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use mbc1::Mbc1;
+use mbc3::Mbc3;
+use mbc5::Mbc5;
+
+/// Failures loading a ROM image from disk, kept typed so front-ends can
+/// tell a bad path from a bad file.
+#[derive(Debug)]
+pub enum CartridgeError {
+    /// The file could not be read at all.
+    Io(std::io::Error),
+    /// The file is shorter than the 0x150-byte header area.
+    TooSmall(usize),
+    /// The file is larger than the ROM size its own header declares.
+    Oversized {
+        len: usize,
+        declared: usize,
+    },
+    /// A RAM image (e.g. a .sav file) does not match the size the header
+    /// declares.
+    RamSizeMismatch {
+        expected: usize,
+        got: usize,
+    },
+    /// The header parsed but describes something we cannot construct
+    /// (unknown cartridge type, bad RAM size code, ...).
+    Invalid(anyhow::Error),
+}
+
+impl std::fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read ROM file: {err}"),
+            Self::TooSmall(len) => {
+                write!(f, "ROM file too small to contain a header ({len} bytes)")
+            }
+            Self::Oversized { len, declared } => write!(
+                f,
+                "ROM file is {len} bytes but its header declares {declared}"
+            ),
+            Self::RamSizeMismatch { expected, got } => write!(
+                f,
+                "cartridge RAM size mismatch: got {got} bytes, header declares {expected}"
+            ),
+            Self::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Invalid(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for CartridgeError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Maps CPU addresses onto the cartridge ROM/RAM and latches bank switches.
+/// Snapshot of an MBC's live banking registers, for debugger memory-map
+/// views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankState {
+    /// ROM bank mapped at 0x4000–0x7FFF.
+    pub rom_bank: u16,
+    /// RAM bank register; on MBC3 values 0x08–0x0C select the RTC instead.
+    pub ram_bank: u8,
+    pub ram_enabled: bool,
+    /// Whether the 0xA000 window currently shows RTC registers (MBC3).
+    pub rtc_selected: bool,
+}
+
+impl Default for BankState {
+    /// An unbanked cart: bank 1 in the switchable window, RAM always on.
+    fn default() -> Self {
+        Self {
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: true,
+            rtc_selected: false,
+        }
+    }
+}
+
+pub(crate) trait Mbc {
+    /// Translate a 0x0000–0x7FFF read into a ROM offset.
+    fn rom_addr(&self, addr: u16) -> usize;
+    /// Translate a 0xA000–0xBFFF access into a RAM offset, or `None` when
+    /// external RAM is disabled.
+    fn ram_addr(&self, addr: u16) -> Option<usize>;
+    /// Handle a write to the 0x0000–0x7FFF control area.
+    fn write_control(&mut self, addr: u16, value: u8);
+    /// Intercept a 0xA000–0xBFFF read when the MBC maps a register there
+    /// instead of RAM (e.g. the MBC3 RTC). Default: nothing mapped.
+    fn read_register(&self, _addr: u16) -> Option<u8> {
+        None
+    }
+    /// Intercept a 0xA000–0xBFFF write; returns true when consumed.
+    fn write_register(&mut self, _addr: u16, _value: u8) -> bool {
+        false
+    }
+    /// Seed the MBC's clock from host time, if it has one.
+    fn set_clock(&mut self, _unix_time: u64) {}
+    /// Whether the rumble motor is currently on. Default: no motor.
+    fn rumble(&self) -> bool {
+        false
+    }
+    /// The live banking registers. Default: the unbanked state.
+    fn bank_state(&self) -> BankState {
+        BankState::default()
+    }
+    /// Snapshot the banking registers for save states.
+    #[cfg(feature = "serde")]
+    fn save_registers(&self) -> Vec<u8>;
+    /// Restore banking registers captured by [`Mbc::save_registers`].
+    #[cfg(feature = "serde")]
+    fn load_registers(&mut self, bytes: &[u8]);
+}
+
+/// ROM-only carts: no banking, no control registers.
+struct NoMbc;
+
+impl Mbc for NoMbc {
+    fn rom_addr(&self, addr: u16) -> usize {
+        addr as usize
+    }
+
+    fn ram_addr(&self, addr: u16) -> Option<usize> {
+        Some((addr - 0xA000) as usize)
+    }
+
+    fn write_control(&mut self, _addr: u16, _value: u8) {}
+
+    #[cfg(feature = "serde")]
+    fn save_registers(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_registers(&mut self, _bytes: &[u8]) {}
+}
+
+/// Which hardware a cartridge targets, from the CGB flag at 0x0143.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareMode {
+    /// No CGB support declared; runs in original Game Boy mode.
+    Dmg,
+    /// 0x80: CGB features, but still runs on a DMG.
+    CgbCompatible,
+    /// 0xC0: requires CGB hardware.
+    CgbOnly,
+}
+
+/// Parsed fields from the cartridge header at 0x0100–0x014F.
+#[derive(Debug, Clone)]
+pub struct Header {
+    pub title: String,
+    pub cartridge_type: u8,
+    pub rom_banks: usize,
+    pub ram_size: usize,
+    /// Wrapping byte sum of the title area (0x0134–0x0143); the CGB boot
+    /// ROM keys its DMG colour-scheme table on this.
+    pub title_checksum: u8,
+    /// Raw CGB flag (0x0143).
+    pub cgb_flag: u8,
+    /// Raw SGB flag (0x0146); 0x03 declares Super Game Boy support.
+    pub sgb_flag: u8,
+}
+
+impl Header {
+    fn parse(rom: &[u8]) -> Result<Self> {
+        if rom.len() < 0x150 {
+            bail!("ROM too small to contain a header ({} bytes)", rom.len());
+        }
+        let cgb_flag = rom[0x143];
+        // A set CGB flag shrinks the title area: 0x0143 holds the flag and
+        // 0x013F–0x0142 the manufacturer code.
+        let title_end = if cgb_flag & 0x80 != 0 { 0x13F } else { 0x144 };
+        let title = rom[0x134..title_end]
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| b as char)
+            .collect();
+        let rom_banks = 2usize << rom[0x148];
+        let ram_size = match rom[0x149] {
+            0x00 => 0,
+            0x01 => 0x800,
+            0x02 => 0x2000,
+            0x03 => 0x8000,
+            0x04 => 0x2_0000,
+            0x05 => 0x1_0000,
+            other => bail!("unknown RAM size code {other:#04X}"),
+        };
+        let title_checksum = rom[0x134..0x144]
+            .iter()
+            .fold(0u8, |sum, &b| sum.wrapping_add(b));
+        Ok(Self {
+            title,
+            cartridge_type: rom[0x147],
+            rom_banks,
+            ram_size,
+            title_checksum,
+            cgb_flag,
+            sgb_flag: rom[0x146],
+        })
+    }
+}
+
+/// A loaded cartridge: ROM image, external RAM and the MBC that maps them.
+pub struct Cartridge {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    mbc: Box<dyn Mbc>,
+    header: Header,
+}
+
+impl Cartridge {
+    /// Parse the header and construct the matching MBC.
+    pub fn new(rom: Vec<u8>) -> Result<Self> {
+        let header = Header::parse(&rom)?;
+        let mbc = Self::build_mbc(&header)?;
+        let ram = vec![0xFF; header.ram_size];
+        Ok(Self {
+            rom,
+            ram,
+            mbc,
+            header,
+        })
+    }
+
+    fn build_mbc(header: &Header) -> Result<Box<dyn Mbc>> {
+        Ok(match header.cartridge_type {
+            0x00 => Box::new(NoMbc),
+            0x01..=0x03 => Box::new(Mbc1::new(header.ram_size)),
+            0x0F..=0x13 => Box::new(Mbc3::new()),
+            0x19..=0x1E => Box::new(Mbc5::new(matches!(
+                header.cartridge_type,
+                0x1C..=0x1E
+            ))),
+            other => bail!("unsupported cartridge type {other:#04X}"),
+        })
+    }
+
+    /// Put the MBC's banking registers back to their power-on state while
+    /// keeping the ROM image and (battery-backed) RAM contents, as pulling
+    /// the power does on hardware.
+    pub fn reset_banking(&mut self) {
+        self.mbc = Self::build_mbc(&self.header).expect("validated at construction");
+    }
+
+    /// Read and construct a cartridge from a ROM file. Dumps shorter than
+    /// the size the header declares are padded out with 0xFF (trimmed
+    /// dumps are common for homebrew); larger files are rejected as
+    /// corrupt rather than silently truncated.
+    pub fn from_path(path: &Path) -> Result<Self, CartridgeError> {
+        let mut rom = std::fs::read(path)?;
+        if rom.len() < 0x150 {
+            return Err(CartridgeError::TooSmall(rom.len()));
+        }
+        // Size codes above 0x08 are invalid; reject them here rather than
+        // let `2 << code` overflow in the header parse or demand a giant
+        // allocation for the padding.
+        let code = rom[0x148];
+        if code > 0x08 {
+            return Err(CartridgeError::Invalid(anyhow::anyhow!(
+                "unknown ROM size code {code:#04X}"
+            )));
+        }
+        let declared = 0x8000usize << code;
+        if rom.len() > declared {
+            return Err(CartridgeError::Oversized {
+                len: rom.len(),
+                declared,
+            });
+        }
+        rom.resize(declared, 0xFF);
+        Self::new(rom).map_err(CartridgeError::Invalid)
+    }
+
+    /// Like [`Cartridge::new`], but install `ram` (e.g. a loaded save file)
+    /// instead of fresh 0xFF-filled RAM. The size must match the header.
+    pub fn new_with_ram(rom: Vec<u8>, ram: Vec<u8>) -> Result<Self> {
+        let mut cart = Self::new(rom)?;
+        cart.install_ram(ram)?;
+        Ok(cart)
+    }
+
+    /// Replace external RAM wholesale (e.g. from a .sav file). The size must
+    /// match the header.
+    pub fn install_ram(&mut self, ram: Vec<u8>) -> Result<(), CartridgeError> {
+        if ram.len() != self.header.ram_size {
+            return Err(CartridgeError::RamSizeMismatch {
+                expected: self.header.ram_size,
+                got: ram.len(),
+            });
+        }
+        self.ram = ram;
+        Ok(())
+    }
+
+    /// The hardware the CGB flag declares this cartridge targets.
+    #[must_use]
+    pub fn hardware_mode(&self) -> HardwareMode {
+        match self.header.cgb_flag {
+            0xC0 => HardwareMode::CgbOnly,
+            0x80 => HardwareMode::CgbCompatible,
+            _ => HardwareMode::Dmg,
+        }
+    }
+
+    /// Whether the SGB flag declares Super Game Boy support.
+    #[must_use]
+    pub fn supports_sgb(&self) -> bool {
+        self.header.sgb_flag == 0x03
+    }
+
+    /// Verify the header checksum at 0x014D: `x = x - rom[i] - 1` over
+    /// 0x0134–0x014C. Real hardware refuses to boot on a mismatch, but we
+    /// only surface it so frontends can warn about corrupt dumps.
+    #[must_use]
+    pub fn verify_header_checksum(&self) -> bool {
+        let computed = self.rom[0x134..=0x14C]
+            .iter()
+            .fold(0u8, |x, &byte| x.wrapping_sub(byte).wrapping_sub(1));
+        computed == self.rom[0x14D]
+    }
+
+    /// Verify the big-endian global checksum at 0x014E–0x014F: the sum of
+    /// every ROM byte except the checksum's own two. Real hardware never
+    /// checks it; this is purely diagnostic.
+    #[must_use]
+    pub fn verify_global_checksum(&self) -> bool {
+        let computed = self
+            .rom
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 0x14E && *i != 0x14F)
+            .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(u16::from(byte)));
+        computed == u16::from_be_bytes([self.rom[0x14E], self.rom[0x14F]])
+    }
+
+    /// The external RAM contents, for battery-backed save persistence.
+    #[must_use]
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Whether the cartridge type includes a battery backing its RAM/RTC.
+    #[must_use]
+    pub fn has_battery(&self) -> bool {
+        matches!(
+            self.header.cartridge_type,
+            0x03 | 0x06 | 0x09 | 0x0D | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E | 0x22 | 0xFF
+        )
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Read from the 0x0000–0x7FFF ROM area.
+    #[must_use]
+    pub fn read_rom(&self, addr: u16) -> u8 {
+        let offset = self.mbc.rom_addr(addr);
+        self.rom.get(offset).copied().unwrap_or(0xFF)
+    }
+
+    /// Whether the MBC5 rumble motor is currently driven. Always `false`
+    /// for cartridges without one.
+    #[must_use]
+    pub fn rumble_state(&self) -> bool {
+        self.mbc.rumble()
+    }
+
+    /// The MBC's current banking registers, for debugger memory-map views.
+    #[must_use]
+    pub fn bank_state(&self) -> BankState {
+        self.mbc.bank_state()
+    }
+
+    /// Seed the cartridge clock (MBC3 RTC) from a host unix timestamp.
+    /// No-op for MBCs without a clock.
+    pub fn set_clock(&mut self, unix_time: u64) {
+        self.mbc.set_clock(unix_time);
+    }
+
+    /// Read from the 0xA000–0xBFFF external RAM area.
+    #[must_use]
+    pub fn read_ram(&self, addr: u16) -> u8 {
+        if let Some(value) = self.mbc.read_register(addr) {
+            return value;
+        }
+        match self.mbc.ram_addr(addr) {
+            Some(offset) if offset < self.ram.len() => self.ram[offset],
+            _ => 0xFF,
+        }
+    }
+
+    /// Write to the 0x0000–0x7FFF control area.
+    pub fn write_rom(&mut self, addr: u16, value: u8) {
+        self.mbc.write_control(addr, value);
+    }
+
+    /// Snapshot external RAM and the MBC's banking registers.
+    #[cfg(feature = "serde")]
+    pub(crate) fn save_banking(&self) -> (Vec<u8>, Vec<u8>) {
+        (self.ram.clone(), self.mbc.save_registers())
+    }
+
+    /// Restore a [`Cartridge::save_banking`] snapshot. The RAM image must
+    /// match the size implied by the header.
+    #[cfg(feature = "serde")]
+    pub(crate) fn load_banking(&mut self, ram: &[u8], registers: &[u8]) -> Result<()> {
+        if ram.len() != self.ram.len() {
+            bail!(
+                "cartridge RAM size mismatch: snapshot has {} bytes, cartridge has {}",
+                ram.len(),
+                self.ram.len()
+            );
+        }
+        self.ram.copy_from_slice(ram);
+        self.mbc.load_registers(registers);
+        Ok(())
+    }
+
+    /// Write to the 0xA000–0xBFFF external RAM area.
+    pub fn write_ram(&mut self, addr: u16, value: u8) {
+        if self.mbc.write_register(addr, value) {
+            return;
+        }
+        if let Some(offset) = self.mbc.ram_addr(addr) {
+            if offset < self.ram.len() {
+                self.ram[offset] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom_with_type(cartridge_type: u8) -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = cartridge_type;
+        rom
+    }
+
+    /// Stamp valid header and global checksums onto `rom`.
+    fn fix_checksums(rom: &mut [u8]) {
+        rom[0x14D] = rom[0x134..=0x14C]
+            .iter()
+            .fold(0u8, |x, &byte| x.wrapping_sub(byte).wrapping_sub(1));
+        rom[0x14E] = 0;
+        rom[0x14F] = 0;
+        let sum = rom
+            .iter()
+            .fold(0u16, |sum, &byte| sum.wrapping_add(u16::from(byte)));
+        rom[0x14E..=0x14F].copy_from_slice(&sum.to_be_bytes());
+    }
+
+    #[test]
+    fn mbc5_reaches_bank_0x1ff_through_the_9_bit_register() {
+        // 512 banks (code 0x08) = 8 MB; tag the last bank's first byte.
+        let mut rom = vec![0u8; 512 * 0x4000];
+        rom[0x147] = 0x19;
+        rom[0x148] = 0x08;
+        rom[0x1FF * 0x4000] = 0x99;
+        let mut cart = Cartridge::new(rom).unwrap();
+
+        cart.write_rom(0x2000, 0xFF); // low 8 bits
+        cart.write_rom(0x3000, 0x01); // bit 8
+        assert_eq!(cart.read_rom(0x4000), 0x99, "bank 0x1FF mapped");
+
+        // Unlike MBC1, bank 0 is selectable in the switchable window.
+        cart.write_rom(0x2000, 0x00);
+        cart.write_rom(0x3000, 0x00);
+        assert_eq!(cart.read_rom(0x4000), 0x00);
+        assert_eq!(cart.read_rom(0x4100), 0x00);
+    }
+
+    #[test]
+    fn mbc5_rumble_carts_drive_the_motor_from_ram_bank_bit_3() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x1C; // MBC5 + rumble
+        let mut cart = Cartridge::new(rom).unwrap();
+        assert!(!cart.rumble_state());
+
+        cart.write_rom(0x4000, 0x08);
+        assert!(cart.rumble_state());
+        cart.write_rom(0x4000, 0x00);
+        assert!(!cart.rumble_state());
+
+        // Plain MBC5 treats bit 3 as RAM banking, never rumble.
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x19;
+        let mut plain = Cartridge::new(rom).unwrap();
+        plain.write_rom(0x4000, 0x08);
+        assert!(!plain.rumble_state());
+    }
+
+    #[test]
+    fn cgb_and_sgb_flags_surface_the_hardware_mode() {
+        let rom = rom_with_type(0x00);
+        let cart = Cartridge::new(rom.clone()).unwrap();
+        assert_eq!(cart.hardware_mode(), HardwareMode::Dmg);
+        assert!(!cart.supports_sgb());
+
+        let mut cgb = rom.clone();
+        cgb[0x143] = 0x80;
+        assert_eq!(
+            Cartridge::new(cgb).unwrap().hardware_mode(),
+            HardwareMode::CgbCompatible
+        );
+
+        let mut cgb_only = rom.clone();
+        cgb_only[0x143] = 0xC0;
+        assert_eq!(
+            Cartridge::new(cgb_only).unwrap().hardware_mode(),
+            HardwareMode::CgbOnly
+        );
+
+        let mut sgb = rom;
+        sgb[0x146] = 0x03;
+        assert!(Cartridge::new(sgb).unwrap().supports_sgb());
+    }
+
+    #[test]
+    fn cgb_flag_shrinks_the_title_area() {
+        let mut rom = rom_with_type(0x00);
+        rom[0x134..0x143].copy_from_slice(b"ABCDEFGHIJKLMNO");
+        let cart = Cartridge::new(rom.clone()).unwrap();
+        assert_eq!(cart.header().title, "ABCDEFGHIJKLMNO");
+
+        rom[0x143] = 0xC0;
+        let cart = Cartridge::new(rom).unwrap();
+        assert_eq!(cart.header().title, "ABCDEFGHIJK");
+    }
+
+    #[test]
+    fn checksums_verify_on_a_well_formed_rom() {
+        let mut rom = rom_with_type(0x00);
+        rom[0x134..0x139].copy_from_slice(b"CHECK");
+        fix_checksums(&mut rom);
+        let cart = Cartridge::new(rom).unwrap();
+        assert!(cart.verify_header_checksum());
+        assert!(cart.verify_global_checksum());
+    }
+
+    #[test]
+    fn corruption_fails_verification_but_not_construction() {
+        let mut rom = rom_with_type(0x00);
+        rom[0x134..0x139].copy_from_slice(b"CHECK");
+        fix_checksums(&mut rom);
+        rom[0x138] = b'X'; // corrupt a title byte both checksums cover
+        let cart = Cartridge::new(rom).unwrap();
+        assert!(!cart.verify_header_checksum());
+        assert!(!cart.verify_global_checksum());
+    }
+
+    #[test]
+    fn parses_rom_only_header() {
+        let cart = Cartridge::new(rom_with_type(0x00)).unwrap();
+        assert_eq!(cart.header().rom_banks, 2);
+        assert_eq!(cart.header().ram_size, 0);
+    }
+
+    #[test]
+    fn rejects_truncated_rom() {
+        assert!(Cartridge::new(vec![0u8; 0x100]).is_err());
+    }
+
+    #[test]
+    fn from_path_loads_and_pads_a_trimmed_dump() {
+        let dir = std::env::temp_dir().join(format!("gboxide-rom-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("minimal.gb");
+        let mut rom = rom_with_type(0x00);
+        rom.truncate(0x150); // header only; the header still declares 32 KiB
+        std::fs::write(&path, &rom).unwrap();
+
+        let cart = Cartridge::from_path(&path).unwrap();
+        assert_eq!(cart.header().rom_banks, 2);
+        assert_eq!(cart.read_rom(0x7FFF), 0xFF, "padded to the declared size");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn from_path_reports_typed_errors() {
+        let dir = std::env::temp_dir().join(format!("gboxide-rom-err-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            Cartridge::from_path(&dir.join("missing.gb")),
+            Err(CartridgeError::Io(_))
+        ));
+
+        let truncated = dir.join("truncated.gb");
+        std::fs::write(&truncated, [0u8; 0x100]).unwrap();
+        assert!(matches!(
+            Cartridge::from_path(&truncated),
+            Err(CartridgeError::TooSmall(0x100))
+        ));
+
+        let oversized = dir.join("oversized.gb");
+        std::fs::write(&oversized, vec![0u8; 0x9000]).unwrap();
+        assert!(matches!(
+            Cartridge::from_path(&oversized),
+            Err(CartridgeError::Oversized {
+                len: 0x9000,
+                declared: 0x8000
+            })
+        ));
+
+        // A corrupt size byte must come back as an error, not overflow
+        // the bank-count shift.
+        let bad_size = dir.join("bad-size.gb");
+        let mut rom = rom_with_type(0x00);
+        rom[0x148] = 0xFF;
+        std::fs::write(&bad_size, &rom).unwrap();
+        assert!(matches!(
+            Cartridge::from_path(&bad_size),
+            Err(CartridgeError::Invalid(_))
+        ));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// 1 MB MBC1 ROM with each bank's first byte stamped with its number.
+    fn mbc1_1mb_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x4000 * 64];
+        rom[0x147] = 0x01;
+        rom[0x148] = 0x05; // 64 banks
+        for bank in 0..64 {
+            rom[bank * 0x4000] = bank as u8;
+        }
+        rom
+    }
+
+    #[test]
+    fn mbc1_upper_bits_select_high_banks() {
+        let mut cart = Cartridge::new(mbc1_1mb_rom()).unwrap();
+        cart.write_rom(0x2000, 0x01); // low 5 bits
+        cart.write_rom(0x4000, 0x01); // upper 2 bits
+        assert_eq!(cart.read_rom(0x4000), 0x21, "bank 0x21 in the 0x4000 window");
+    }
+
+    #[test]
+    fn mbc1_mode_1_banks_the_fixed_window() {
+        let mut cart = Cartridge::new(mbc1_1mb_rom()).unwrap();
+        cart.write_rom(0x4000, 0x01);
+
+        // Mode 0: the 0x0000 window stays on bank 0.
+        assert_eq!(cart.read_rom(0x0000), 0x00);
+
+        cart.write_rom(0x6000, 0x01);
+        assert_eq!(cart.read_rom(0x0000), 0x20, "mode 1 maps bank 0x20 at 0x0000");
+        assert_eq!(cart.read_rom(0x4000), 0x21, "switchable window unchanged");
+    }
+
+    #[test]
+    fn mbc1_mode_1_banks_external_ram() {
+        let mut rom = mbc1_1mb_rom();
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x03; // 32 KiB RAM, 4 banks
+        let mut cart = Cartridge::new(rom).unwrap();
+        cart.write_rom(0x0000, 0x0A); // enable RAM
+        cart.write_rom(0x6000, 0x01); // mode 1
+
+        cart.write_rom(0x4000, 0x00);
+        cart.write_ram(0xA000, 0x11);
+        cart.write_rom(0x4000, 0x02);
+        cart.write_ram(0xA000, 0x22);
+
+        cart.write_rom(0x4000, 0x00);
+        assert_eq!(cart.read_ram(0xA000), 0x11);
+        cart.write_rom(0x4000, 0x02);
+        assert_eq!(cart.read_ram(0xA000), 0x22);
+    }
+
+    #[test]
+    fn mbc1_small_ram_ignores_the_ram_bank_register() {
+        let mut rom = mbc1_1mb_rom();
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x02; // 8 KiB RAM: a single, unbanked chip
+        let mut cart = Cartridge::new(rom).unwrap();
+        cart.write_rom(0x0000, 0x0A); // enable RAM
+        cart.write_rom(0x6000, 0x01); // mode 1
+
+        cart.write_rom(0x4000, 0x00);
+        cart.write_ram(0xA000, 0x11);
+        cart.write_rom(0x4000, 0x02);
+        assert_eq!(cart.read_ram(0xA000), 0x11, "bank writes must not move RAM");
+        cart.write_ram(0xA000, 0x22);
+        cart.write_rom(0x4000, 0x00);
+        assert_eq!(cart.read_ram(0xA000), 0x22);
+    }
+
+    #[test]
+    fn mbc1_switches_rom_banks() {
+        let mut rom = vec![0u8; 0x4000 * 4];
+        rom[0x147] = 0x01;
+        rom[0x148] = 0x01; // 4 banks
+        rom[0x4000 * 2] = 0xAA;
+        let mut cart = Cartridge::new(rom).unwrap();
+        cart.write_rom(0x2000, 0x02);
+        assert_eq!(cart.read_rom(0x4000), 0xAA);
+    }
+
+    #[test]
+    fn new_with_ram_installs_preloaded_contents() {
+        let mut rom = rom_with_type(0x03); // MBC1 + RAM + battery
+        rom[0x149] = 0x02; // 8 KiB
+        let mut ram = vec![0u8; 0x2000];
+        ram[0x0123] = 0xAB;
+
+        let mut cart = Cartridge::new_with_ram(rom, ram).unwrap();
+        cart.write_rom(0x0000, 0x0A); // enable RAM
+        assert_eq!(cart.read_ram(0xA123), 0xAB);
+    }
+
+    #[test]
+    fn new_with_ram_rejects_wrong_size() {
+        let mut rom = rom_with_type(0x03);
+        rom[0x149] = 0x02;
+        assert!(Cartridge::new_with_ram(rom, vec![0u8; 0x800]).is_err());
+    }
+
+    #[test]
+    fn install_ram_checks_the_size_against_the_header() {
+        let mut rom = rom_with_type(0x03);
+        rom[0x149] = 0x02; // 8 KiB
+        let mut cart = Cartridge::new(rom).unwrap();
+        assert_eq!(cart.ram().len(), 0x2000, "always exactly the header size");
+
+        assert!(matches!(
+            cart.install_ram(vec![0u8; 0x800]),
+            Err(CartridgeError::RamSizeMismatch {
+                expected: 0x2000,
+                got: 0x800
+            })
+        ));
+        assert!(cart.install_ram(vec![0x5A; 0x2000]).is_ok());
+        assert_eq!(cart.ram().len(), 0x2000);
+    }
+
+    /// MBC3 + RAM + battery + timer cartridge.
+    fn mbc3_rtc_cart() -> Cartridge {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x10;
+        rom[0x149] = 0x02;
+        let mut cart = Cartridge::new(rom).unwrap();
+        cart.write_rom(0x0000, 0x0A); // enable RAM/RTC access
+        cart
+    }
+
+    #[test]
+    fn mbc3_rtc_latch_freezes_registers() {
+        let mut cart = mbc3_rtc_cart();
+        cart.set_clock(90); // 00:01:30
+        cart.write_rom(0x6000, 0x00);
+        cart.write_rom(0x6000, 0x01); // latch
+
+        cart.write_rom(0x4000, 0x08); // seconds register
+        assert_eq!(cart.read_ram(0xA000), 30);
+        cart.write_rom(0x4000, 0x09); // minutes register
+        assert_eq!(cart.read_ram(0xA000), 1);
+
+        // The clock moves on but reads stay latched until the next sequence.
+        cart.set_clock(3600);
+        cart.write_rom(0x4000, 0x08);
+        assert_eq!(cart.read_ram(0xA000), 30);
+        cart.write_rom(0x6000, 0x00);
+        cart.write_rom(0x6000, 0x01);
+        assert_eq!(cart.read_ram(0xA000), 0);
+        cart.write_rom(0x4000, 0x0A); // hours register
+        assert_eq!(cart.read_ram(0xA000), 1);
+    }
+
+    #[test]
+    fn mbc3_rtc_halt_bit_stops_the_clock() {
+        let mut cart = mbc3_rtc_cart();
+        cart.set_clock(30);
+        cart.write_rom(0x4000, 0x0C); // day-high register
+        cart.write_ram(0xA000, 0x40); // set halt
+
+        cart.set_clock(45); // must be ignored while halted
+        cart.write_rom(0x6000, 0x00);
+        cart.write_rom(0x6000, 0x01);
+        cart.write_rom(0x4000, 0x08);
+        assert_eq!(cart.read_ram(0xA000), 30);
+    }
+
+    #[test]
+    fn mbc3_bank_select_routes_between_ram_and_rtc() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x147] = 0x10;
+        rom[0x149] = 0x03; // 32 KiB: four RAM banks
+        let mut cart = Cartridge::new(rom).unwrap();
+        cart.write_rom(0x0000, 0x0A);
+
+        cart.write_rom(0x4000, 0x02); // RAM bank 2
+        cart.write_ram(0xA000, 0x5A);
+        assert_eq!(cart.read_ram(0xA000), 0x5A);
+
+        cart.write_rom(0x4000, 0x08); // RTC seconds
+        cart.write_ram(0xA000, 42);
+        cart.write_rom(0x6000, 0x00);
+        cart.write_rom(0x6000, 0x01);
+        assert_eq!(cart.read_ram(0xA000), 42, "reads route to the RTC");
+
+        cart.write_rom(0x4000, 0x02);
+        assert_eq!(cart.read_ram(0xA000), 0x5A, "RAM bank 2 untouched");
+        cart.write_rom(0x4000, 0x00);
+        assert_eq!(cart.read_ram(0xA000), 0xFF, "bank 0 was never written");
+    }
+
+    #[test]
+    fn mbc3_rtc_bank_does_not_alias_external_ram() {
+        let mut cart = mbc3_rtc_cart();
+        cart.write_rom(0x4000, 0x00);
+        cart.write_ram(0xA000, 0x77);
+        cart.write_rom(0x4000, 0x08);
+        cart.write_ram(0xA000, 0x12); // RTC seconds, not RAM
+        cart.write_rom(0x4000, 0x00);
+        assert_eq!(cart.read_ram(0xA000), 0x77);
+    }
+}
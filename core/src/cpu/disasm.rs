@@ -0,0 +1,129 @@
+//! One-instruction disassembler built on the opcode tables' mnemonics.
+//!
+//! Mnemonics use lowercase placeholders for operands (`n` imm8, `nn` imm16,
+//! `e` signed relative); this module resolves them by reading from the bus.
+
+use crate::cpu::opcodes;
+use crate::cpu::Cpu;
+use crate::mmu::Mmu;
+
+impl Cpu {
+    /// Decode the instruction at `addr` into its mnemonic with resolved
+    /// operands (e.g. `JP $C350`, `LD A,$1F`) and its length in bytes.
+    /// Illegal opcodes come back as `DB $xx` so a trace can step past them.
+    #[must_use]
+    pub fn disassemble(mmu: &Mmu, addr: u16) -> (String, u16) {
+        let byte = mmu.read(addr);
+
+        if byte == 0xCB {
+            let sub = mmu.read(addr.wrapping_add(1));
+            return match opcodes::cb_opcode(sub) {
+                // CB-prefixed instructions carry no immediates.
+                Some(op) => (op.mnemonic.to_string(), 2),
+                None => (format!("DB $CB,${sub:02X}"), 2),
+            };
+        }
+
+        let Some(op) = opcodes::opcode(byte) else {
+            return (format!("DB ${byte:02X}"), 1);
+        };
+
+        if op.mnemonic.contains("nn") {
+            let lo = mmu.read(addr.wrapping_add(1));
+            let hi = mmu.read(addr.wrapping_add(2));
+            let value = u16::from_le_bytes([lo, hi]);
+            return (op.mnemonic.replace("nn", &format!("${value:04X}")), 3);
+        }
+
+        if op.mnemonic.contains('e') {
+            let offset = mmu.read(addr.wrapping_add(1)) as i8;
+            let text = if op.mnemonic.starts_with("JR") {
+                // Resolve the branch target so traces read like a listing.
+                let target = addr.wrapping_add(2).wrapping_add(offset as u16);
+                op.mnemonic.replace('e', &format!("${target:04X}"))
+            } else {
+                // ADD SP,e and LD HL,SP+e: show the signed displacement.
+                op.mnemonic.replace("+e", "e").replace('e', &format!("{offset:+}"))
+            };
+            return (text, 2);
+        }
+
+        if op.mnemonic.contains('n') {
+            let value = mmu.read(addr.wrapping_add(1));
+            return (op.mnemonic.replace('n', &format!("${value:02X}")), 2);
+        }
+
+        // STOP is encoded with a one-byte padding operand.
+        let len = if byte == 0x10 { 2 } else { 1 };
+        (op.mnemonic.to_string(), len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::Cartridge;
+
+    fn mmu_with_code(code: &[u8]) -> Mmu {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x100 + code.len()].copy_from_slice(code);
+        Mmu::new(Cartridge::new(rom).unwrap())
+    }
+
+    #[test]
+    fn disassembles_a_sequence_with_immediates() {
+        let mmu = mmu_with_code(&[
+            0x00, // NOP
+            0x3E, 0x1F, // LD A,$1F
+            0xC3, 0x50, 0xC3, // JP $C350
+            0x08, 0x34, 0x12, // LD ($1234),SP
+            0xE0, 0x44, // LDH ($44),A
+        ]);
+        let expected = [
+            ("NOP", 1u16),
+            ("LD A,$1F", 2),
+            ("JP $C350", 3),
+            ("LD ($1234),SP", 3),
+            ("LDH ($44),A", 2),
+        ];
+        let mut addr = 0x0100;
+        for (text, len) in expected {
+            assert_eq!(Cpu::disassemble(&mmu, addr), (text.to_string(), len));
+            addr += len;
+        }
+    }
+
+    #[test]
+    fn resolves_relative_branch_targets() {
+        let mmu = mmu_with_code(&[0x18, 0xFE, 0x20, 0x03]);
+        assert_eq!(
+            Cpu::disassemble(&mmu, 0x0100),
+            ("JR $0100".to_string(), 2)
+        );
+        assert_eq!(
+            Cpu::disassemble(&mmu, 0x0102),
+            ("JR NZ,$0107".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn formats_signed_sp_displacements() {
+        let mmu = mmu_with_code(&[0xE8, 0xFB, 0xF8, 0x05]);
+        assert_eq!(Cpu::disassemble(&mmu, 0x0100), ("ADD SP,-5".to_string(), 2));
+        assert_eq!(
+            Cpu::disassemble(&mmu, 0x0102),
+            ("LD HL,SP+5".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn handles_cb_prefix_and_illegal_bytes() {
+        let mmu = mmu_with_code(&[0xCB, 0xFE, 0xD3, 0x10, 0x00]);
+        assert_eq!(
+            Cpu::disassemble(&mmu, 0x0100),
+            ("SET 7,(HL)".to_string(), 2)
+        );
+        assert_eq!(Cpu::disassemble(&mmu, 0x0102), ("DB $D3".to_string(), 1));
+        assert_eq!(Cpu::disassemble(&mmu, 0x0103), ("STOP".to_string(), 2));
+    }
+}
@@ -0,0 +1,277 @@
+//! M-cycle stepped execution.
+//!
+//! [`Cpu::step`] runs a whole instruction and lets the caller advance the
+//! bus by the total afterwards, so peripherals see every memory access as
+//! if it happened at the start of the instruction. [`Cpu::step_m_cycle`]
+//! instead ticks the bus by one M-cycle (4 T-cycles) around each bus
+//! access, so the timer, DMA unit and PPU observe mid-instruction reads
+//! and writes at the right sub-instruction time. The load/store and stack
+//! opcodes are micro-coded so far; everything else falls back to
+//! whole-instruction timing.
+
+use super::{opcodes, Cpu, CpuError};
+use crate::mmu::Mmu;
+
+impl Cpu {
+    /// Execute one instruction, advancing the bus one M-cycle at a time.
+    /// The caller must *not* call [`Mmu::step`] afterwards — unlike
+    /// [`Cpu::step`], the bus time is already paid here. Returns the
+    /// T-cycles consumed.
+    pub fn step_m_cycle(&mut self, mmu: &mut Mmu) -> Result<usize, CpuError> {
+        if self.stopped && !self.check_stop_wake(mmu) {
+            return Ok(0);
+        }
+        if let Some(cycles) = self.service_interrupts(mmu) {
+            mmu.step(cycles)?;
+            return Ok(cycles);
+        }
+        if self.halted {
+            self.update_ime();
+            mmu.step(4)?;
+            return Ok(4);
+        }
+
+        let byte = self.fetch_opcode(mmu);
+        mmu.step(4)?; // the opcode-fetch M-cycle
+
+        if let Some(cycles) = self.exec_micro(mmu, byte)? {
+            self.update_ime();
+            return Ok(cycles);
+        }
+
+        // Not micro-coded yet: execute whole, then pay the remaining time.
+        let cycles = if byte == 0xCB {
+            let sub = self.fetch8(mmu);
+            let op = opcodes::cb_opcode(sub).ok_or(CpuError::UnimplementedCbOpcode {
+                opcode: sub,
+                pc: self.regs.pc.wrapping_sub(2),
+            })?;
+            (op.exec)(self, mmu)?;
+            op.base_cycles as usize
+        } else {
+            let Some(op) = opcodes::opcode(byte) else {
+                if self.log_unimplemented {
+                    self.record_unimplemented(byte);
+                    self.update_ime();
+                    return Ok(4);
+                }
+                return Err(CpuError::UnimplementedOpcode {
+                    opcode: byte,
+                    pc: self.regs.pc.wrapping_sub(1),
+                });
+            };
+            let branched = (op.exec)(self, mmu)?;
+            let mut cycles = op.base_cycles as usize;
+            if branched {
+                cycles += op.conditional_cycles as usize;
+            }
+            cycles
+        };
+        mmu.step(cycles - 4)?;
+        self.update_ime();
+        Ok(cycles)
+    }
+
+    /// Run `byte` with a bus tick after each access if it is one of the
+    /// micro-coded opcodes. Returns the total T-cycles including the
+    /// already-ticked fetch, or `None` when the opcode is not covered.
+    fn exec_micro(&mut self, mmu: &mut Mmu, byte: u8) -> Result<Option<usize>, CpuError> {
+        let cycles = match byte {
+            // LD (BC),A / LD (DE),A
+            0x02 => {
+                mmu.write(self.regs.bc(), self.regs.a);
+                mmu.step(4)?;
+                8
+            }
+            0x12 => {
+                mmu.write(self.regs.de(), self.regs.a);
+                mmu.step(4)?;
+                8
+            }
+            // LD A,(BC) / LD A,(DE)
+            0x0A => {
+                self.regs.a = mmu.read(self.regs.bc());
+                mmu.step(4)?;
+                8
+            }
+            0x1A => {
+                self.regs.a = mmu.read(self.regs.de());
+                mmu.step(4)?;
+                8
+            }
+            // LD (nn),SP
+            0x08 => {
+                let lo = self.fetch8(mmu);
+                mmu.step(4)?;
+                let hi = self.fetch8(mmu);
+                mmu.step(4)?;
+                let addr = u16::from_le_bytes([lo, hi]);
+                let [sp_hi, sp_lo] = self.regs.sp.to_be_bytes();
+                mmu.write(addr, sp_lo);
+                mmu.step(4)?;
+                mmu.write(addr.wrapping_add(1), sp_hi);
+                mmu.step(4)?;
+                20
+            }
+            // LD (HL+),A / LD (HL-),A
+            0x22 | 0x32 => {
+                let hl = self.regs.hl();
+                mmu.write(hl, self.regs.a);
+                mmu.step(4)?;
+                self.regs.set_hl(if byte == 0x22 {
+                    hl.wrapping_add(1)
+                } else {
+                    hl.wrapping_sub(1)
+                });
+                8
+            }
+            // LD A,(HL+) / LD A,(HL-)
+            0x2A | 0x3A => {
+                let hl = self.regs.hl();
+                self.regs.a = mmu.read(hl);
+                mmu.step(4)?;
+                self.regs.set_hl(if byte == 0x2A {
+                    hl.wrapping_add(1)
+                } else {
+                    hl.wrapping_sub(1)
+                });
+                8
+            }
+            // LD (HL),n
+            0x36 => {
+                let n = self.fetch8(mmu);
+                mmu.step(4)?;
+                mmu.write(self.regs.hl(), n);
+                mmu.step(4)?;
+                12
+            }
+            // LD r,(HL)
+            0x46 | 0x4E | 0x56 | 0x5E | 0x66 | 0x6E | 0x7E => {
+                let value = mmu.read(self.regs.hl());
+                mmu.step(4)?;
+                self.write_r8((byte >> 3) & 0x07, value);
+                8
+            }
+            // LD (HL),r
+            0x70..=0x75 | 0x77 => {
+                mmu.write(self.regs.hl(), self.read_r8(byte & 0x07));
+                mmu.step(4)?;
+                8
+            }
+            // LDH (n),A / LDH A,(n)
+            0xE0 => {
+                let n = self.fetch8(mmu);
+                mmu.step(4)?;
+                mmu.write(0xFF00 | u16::from(n), self.regs.a);
+                mmu.step(4)?;
+                12
+            }
+            0xF0 => {
+                let n = self.fetch8(mmu);
+                mmu.step(4)?;
+                self.regs.a = mmu.read(0xFF00 | u16::from(n));
+                mmu.step(4)?;
+                12
+            }
+            // LDH (C),A / LDH A,(C)
+            0xE2 => {
+                mmu.write(0xFF00 | u16::from(self.regs.c), self.regs.a);
+                mmu.step(4)?;
+                8
+            }
+            0xF2 => {
+                self.regs.a = mmu.read(0xFF00 | u16::from(self.regs.c));
+                mmu.step(4)?;
+                8
+            }
+            // LD (nn),A / LD A,(nn)
+            0xEA | 0xFA => {
+                let lo = self.fetch8(mmu);
+                mmu.step(4)?;
+                let hi = self.fetch8(mmu);
+                mmu.step(4)?;
+                let addr = u16::from_le_bytes([lo, hi]);
+                if byte == 0xEA {
+                    mmu.write(addr, self.regs.a);
+                } else {
+                    self.regs.a = mmu.read(addr);
+                }
+                mmu.step(4)?;
+                16
+            }
+            // PUSH rr: an internal delay cycle, then the high byte, then
+            // the low byte.
+            0xC5 | 0xD5 | 0xE5 | 0xF5 => {
+                mmu.step(4)?;
+                let [hi, lo] = self.read_r16_stack((byte >> 4) & 0x03).to_be_bytes();
+                self.regs.sp = self.regs.sp.wrapping_sub(1);
+                mmu.write(self.regs.sp, hi);
+                mmu.step(4)?;
+                self.regs.sp = self.regs.sp.wrapping_sub(1);
+                mmu.write(self.regs.sp, lo);
+                mmu.step(4)?;
+                16
+            }
+            // POP rr: low byte, then high byte.
+            0xC1 | 0xD1 | 0xE1 | 0xF1 => {
+                let lo = mmu.read(self.regs.sp);
+                self.regs.sp = self.regs.sp.wrapping_add(1);
+                mmu.step(4)?;
+                let hi = mmu.read(self.regs.sp);
+                self.regs.sp = self.regs.sp.wrapping_add(1);
+                mmu.step(4)?;
+                self.write_r16_stack((byte >> 4) & 0x03, u16::from_be_bytes([hi, lo]));
+                12
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(cycles))
+    }
+
+    /// 8-bit register by SM83 encoding (0=B … 7=A; 6 is `(HL)`, which the
+    /// micro-coded opcodes handle themselves).
+    fn read_r8(&self, index: u8) -> u8 {
+        match index {
+            0 => self.regs.b,
+            1 => self.regs.c,
+            2 => self.regs.d,
+            3 => self.regs.e,
+            4 => self.regs.h,
+            5 => self.regs.l,
+            7 => self.regs.a,
+            _ => unreachable!("(HL) is not a register"),
+        }
+    }
+
+    fn write_r8(&mut self, index: u8, value: u8) {
+        match index {
+            0 => self.regs.b = value,
+            1 => self.regs.c = value,
+            2 => self.regs.d = value,
+            3 => self.regs.e = value,
+            4 => self.regs.h = value,
+            5 => self.regs.l = value,
+            7 => self.regs.a = value,
+            _ => unreachable!("(HL) is not a register"),
+        }
+    }
+
+    /// 16-bit register pair by PUSH/POP encoding (0=BC, 1=DE, 2=HL, 3=AF).
+    fn read_r16_stack(&self, index: u8) -> u16 {
+        match index {
+            0 => self.regs.bc(),
+            1 => self.regs.de(),
+            2 => self.regs.hl(),
+            _ => self.regs.af(),
+        }
+    }
+
+    fn write_r16_stack(&mut self, index: u8, value: u16) {
+        match index {
+            0 => self.regs.set_bc(value),
+            1 => self.regs.set_de(value),
+            2 => self.regs.set_hl(value),
+            _ => self.regs.set_af(value),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! SM83 CPU core: fetch/decode/execute loop and interrupt servicing.
+
+mod disasm;
+mod micro;
+pub mod opcodes;
+pub mod registers;
+
+use crate::interrupts::Interrupt;
+use crate::mmu::{Mmu, MmuError};
+use registers::{Registers, FLAG_C, FLAG_H, FLAG_N, FLAG_Z};
+
+/// Errors [`Cpu::step`] can surface. Typed so callers can distinguish a ROM
+/// hitting an illegal opcode from the bus rejecting an access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuError {
+    /// The fetched byte has no entry in the opcode table.
+    UnimplementedOpcode { opcode: u8, pc: u16 },
+    /// A CB-prefixed byte has no entry in the CB table.
+    UnimplementedCbOpcode { opcode: u8, pc: u16 },
+    /// The bus rejected an access (strict-mode [`Mmu`]).
+    Bus(MmuError),
+}
+
+impl std::fmt::Display for CpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnimplementedOpcode { opcode, pc } => {
+                write!(f, "unimplemented opcode {opcode:#04X} at {pc:#06X}")
+            }
+            Self::UnimplementedCbOpcode { opcode, pc } => {
+                write!(f, "unimplemented CB-prefixed opcode {opcode:#04X} at {pc:#06X}")
+            }
+            Self::Bus(err) => write!(f, "bus error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CpuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Bus(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MmuError> for CpuError {
+    fn from(err: MmuError) -> Self {
+        Self::Bus(err)
+    }
+}
+
+/// The emulated CPU. Owns the register file and interrupt-enable state;
+/// memory is accessed through the [`Mmu`] passed to [`Cpu::step`].
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cpu {
+    pub regs: Registers,
+    /// Interrupt master enable. Private so every path that flips it goes
+    /// through the EI-delay machinery; read it via [`Cpu::ime`].
+    ime: bool,
+    /// Countdown for a scheduled IME enable (EI takes effect one
+    /// instruction late); 0 means nothing scheduled.
+    ime_delay: u8,
+    pub halted: bool,
+    pub stopped: bool,
+    /// Emit a `tracing::trace!` event per step when enabled (CLI `--debug`).
+    /// Host configuration, not emulated state, so save states skip it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub trace: bool,
+    /// Step number for trace output. Per-instance so independent CPUs never
+    /// share state; host-side, so save states skip it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trace_steps: usize,
+    /// Post-mortem ring of the last executed (pc, opcode) pairs; `None`
+    /// until [`Cpu::enable_trace_ring`]. Host-side, so save states skip it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trace_ring: Option<std::collections::VecDeque<(u16, u8)>>,
+    /// Capacity of the trace ring once enabled.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trace_ring_capacity: usize,
+    /// When set, unknown opcodes execute as 4-cycle NOPs and are tallied
+    /// instead of aborting (CLI `--trace-unimplemented`), so a ROM's full
+    /// instruction needs can be surveyed in one run. Host configuration,
+    /// not emulated state.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub log_unimplemented: bool,
+    /// Per-opcode tally of unimplemented hits: first PC seen and count.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unimplemented_seen: std::collections::BTreeMap<u8, (u16, usize)>,
+}
+
+impl Cpu {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A CPU already in the DMG post-boot-ROM state, for users who skip the
+    /// boot ROM entirely.
+    #[must_use]
+    pub fn new_post_boot() -> Self {
+        let mut cpu = Self::new();
+        cpu.reset_post_boot();
+        cpu
+    }
+
+    /// Set registers to the DMG post-boot-ROM state.
+    pub fn reset_post_boot(&mut self) {
+        self.regs.a = 0x01;
+        self.regs.f = 0xB0;
+        self.regs.b = 0x00;
+        self.regs.c = 0x13;
+        self.regs.d = 0x00;
+        self.regs.e = 0xD8;
+        self.regs.h = 0x01;
+        self.regs.l = 0x4D;
+        self.regs.sp = 0xFFFE;
+        self.regs.pc = 0x0100;
+        self.ime = false;
+        self.halted = false;
+        self.stopped = false;
+    }
+
+    /// Execute one instruction (or service one interrupt) and return the
+    /// number of T-cycles consumed.
+    pub fn step(&mut self, mmu: &mut Mmu) -> Result<usize, CpuError> {
+        if self.trace {
+            let n = self.trace_steps;
+            self.trace_steps += 1;
+            let (asm, _) = Cpu::disassemble(mmu, self.regs.pc);
+            tracing::trace!(
+                step = n,
+                pc = format_args!("{:04X}", self.regs.pc),
+                asm = %asm,
+                af = format_args!("{:04X}", self.regs.af()),
+                halted = self.halted,
+            );
+        }
+
+        if self.stopped && !self.check_stop_wake(mmu) {
+            return Ok(0);
+        }
+
+        if let Some(cycles) = self.service_interrupts(mmu) {
+            return Ok(cycles);
+        }
+
+        if self.halted {
+            // A scheduled IME enable must still land while halted, so a
+            // pending interrupt can wake us into its handler next step.
+            self.update_ime();
+            return Ok(4);
+        }
+
+        let byte = self.fetch_opcode(mmu);
+
+        if byte == 0xCB {
+            let sub = self.fetch8(mmu);
+            let op = opcodes::cb_opcode(sub).ok_or(CpuError::UnimplementedCbOpcode {
+                opcode: sub,
+                pc: self.regs.pc.wrapping_sub(2),
+            })?;
+            (op.exec)(self, mmu)?;
+            self.update_ime();
+            return Ok(op.base_cycles as usize);
+        }
+
+        let Some(op) = opcodes::opcode(byte) else {
+            if self.log_unimplemented {
+                self.record_unimplemented(byte);
+                self.update_ime();
+                return Ok(4);
+            }
+            return Err(CpuError::UnimplementedOpcode {
+                opcode: byte,
+                pc: self.regs.pc.wrapping_sub(1),
+            });
+        };
+
+        let branched = (op.exec)(self, mmu)?;
+        let mut cycles = op.base_cycles as usize;
+        if branched {
+            cycles += op.conditional_cycles as usize;
+        }
+        self.update_ime();
+        Ok(cycles)
+    }
+
+    /// Tally an unimplemented opcode hit, logging each distinct opcode the
+    /// first time it appears.
+    fn record_unimplemented(&mut self, byte: u8) {
+        let pc = self.regs.pc.wrapping_sub(1);
+        let entry = self.unimplemented_seen.entry(byte).or_insert((pc, 0));
+        entry.1 += 1;
+        if entry.1 == 1 {
+            tracing::warn!(
+                opcode = format_args!("{byte:#04X}"),
+                pc = format_args!("{pc:#06X}"),
+                "unimplemented opcode, continuing as NOP"
+            );
+        }
+    }
+
+    /// The unimplemented opcodes hit while [`Cpu::log_unimplemented`] was
+    /// set, as (opcode, first PC, count), sorted by opcode.
+    #[must_use]
+    pub fn unimplemented_report(&self) -> Vec<(u8, u16, usize)> {
+        self.unimplemented_seen
+            .iter()
+            .map(|(&opcode, &(pc, count))| (opcode, pc, count))
+            .collect()
+    }
+
+    /// Cycle cost of the instruction at `addr`, without executing it: the
+    /// base count from the opcode table, so conditional instructions report
+    /// their not-taken cost. Lets a scheduler align audio/video work with
+    /// the upcoming instruction. Illegal opcodes count as a 4-cycle fetch.
+    #[must_use]
+    pub fn peek_cycles(mmu: &Mmu, addr: u16) -> u32 {
+        let byte = mmu.read(addr);
+        if byte == 0xCB {
+            let sub = mmu.read(addr.wrapping_add(1));
+            opcodes::cb_opcode(sub).map_or(8, |op| u32::from(op.base_cycles))
+        } else {
+            opcodes::opcode(byte).map_or(4, |op| u32::from(op.base_cycles))
+        }
+    }
+
+    /// Keep a ring of the last `n` executed (pc, opcode) pairs for
+    /// post-mortem analysis when a step errors out. `n` is clamped to at
+    /// least 1; enabling again clears previous entries.
+    pub fn enable_trace_ring(&mut self, n: usize) {
+        self.trace_ring_capacity = n.max(1);
+        self.trace_ring = Some(std::collections::VecDeque::with_capacity(
+            self.trace_ring_capacity,
+        ));
+    }
+
+    /// The recorded (pc, opcode) pairs, oldest first. Empty when the ring
+    /// was never enabled.
+    #[must_use]
+    pub fn recent_instructions(&self) -> Vec<(u16, u8)> {
+        self.trace_ring
+            .as_ref()
+            .map(|ring| ring.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the interrupt master enable is currently on.
+    #[must_use]
+    pub fn ime(&self) -> bool {
+        self.ime
+    }
+
+    /// Turn IME on immediately, bypassing the EI delay (RETI).
+    pub fn enable_ime(&mut self) {
+        self.ime = true;
+        self.ime_delay = 0;
+    }
+
+    /// Schedule IME to turn on after the *next* instruction completes.
+    pub fn schedule_enable_ime(&mut self) {
+        self.ime_delay = 2;
+    }
+
+    /// Turn IME off immediately, cancelling any scheduled enable (DI).
+    pub fn disable_ime(&mut self) {
+        self.ime = false;
+        self.ime_delay = 0;
+    }
+
+    /// Commit a scheduled IME enable. Called at the end of every step.
+    fn update_ime(&mut self) {
+        if self.ime_delay > 0 {
+            self.ime_delay -= 1;
+            if self.ime_delay == 0 {
+                self.ime = true;
+            }
+        }
+    }
+
+    /// While stopped the machine is fully stalled: no instruction runs and
+    /// the zero cycle count freezes the timer, PPU and APU too. A joypad
+    /// press (IF bit 4) is the only thing that resumes. Returns whether we
+    /// are running.
+    pub(crate) fn check_stop_wake(&mut self, mmu: &Mmu) -> bool {
+        if mmu.interrupt_flags() & Interrupt::Joypad.mask() != 0 {
+            self.stopped = false;
+        }
+        !self.stopped
+    }
+
+    /// If an enabled interrupt is pending, dispatch it. Always clears the
+    /// halted state when any interrupt is pending, even with IME off.
+    fn service_interrupts(&mut self, mmu: &mut Mmu) -> Option<usize> {
+        let pending = mmu.interrupt_flags() & mmu.interrupt_enable() & 0x1F;
+        if pending == 0 {
+            return None;
+        }
+        self.halted = false;
+        if !self.ime {
+            return None;
+        }
+        self.ime = false;
+
+        // Hardware dispatch takes 5 M-cycles: two internal NOPs, the PC
+        // push split over two cycles, then the vector jump. IE is re-read
+        // between the two push bytes, so a stack near 0xFFFF lets the
+        // high-byte push rewrite IE and redirect — or cancel — the
+        // dispatch (the interrupt-dispatch bug).
+        let [hi, lo] = self.regs.pc.to_be_bytes();
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        mmu.write(self.regs.sp, hi);
+        let pending = mmu.interrupt_flags() & mmu.interrupt_enable() & 0x1F;
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        mmu.write(self.regs.sp, lo);
+
+        match Interrupt::highest_priority(pending) {
+            Some(int) => {
+                mmu.clear_interrupt(int);
+                self.regs.pc = int.vector();
+            }
+            // Cancelled mid-dispatch: no IF bit is cleared and the CPU
+            // lands on 0x0000.
+            None => self.regs.pc = 0x0000,
+        }
+        Some(20)
+    }
+
+    // --- fetch/stack helpers -------------------------------------------------
+
+    /// Read the opcode byte at PC, record it in the trace ring, and advance
+    /// PC. Shared by [`Cpu::step`] and [`Cpu::step_m_cycle`].
+    fn fetch_opcode(&mut self, mmu: &Mmu) -> u8 {
+        let byte = mmu.read(self.regs.pc);
+        if let Some(ring) = &mut self.trace_ring {
+            // Record before decoding so an illegal opcode is the last entry.
+            if ring.len() == self.trace_ring_capacity {
+                ring.pop_front();
+            }
+            ring.push_back((self.regs.pc, byte));
+        }
+        self.regs.pc = self.regs.pc.wrapping_add(1);
+        byte
+    }
+
+    pub(crate) fn fetch8(&mut self, mmu: &Mmu) -> u8 {
+        let v = mmu.read(self.regs.pc);
+        self.regs.pc = self.regs.pc.wrapping_add(1);
+        v
+    }
+
+    pub(crate) fn fetch16(&mut self, mmu: &Mmu) -> u16 {
+        let lo = self.fetch8(mmu);
+        let hi = self.fetch8(mmu);
+        u16::from_le_bytes([lo, hi])
+    }
+
+    pub(crate) fn push16(&mut self, mmu: &mut Mmu, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        mmu.write(self.regs.sp, hi);
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        mmu.write(self.regs.sp, lo);
+    }
+
+    pub(crate) fn pop16(&mut self, mmu: &Mmu) -> u16 {
+        let lo = mmu.read(self.regs.sp);
+        self.regs.sp = self.regs.sp.wrapping_add(1);
+        let hi = mmu.read(self.regs.sp);
+        self.regs.sp = self.regs.sp.wrapping_add(1);
+        u16::from_be_bytes([hi, lo])
+    }
+
+    // --- 8-bit ALU helpers ---------------------------------------------------
+
+    pub(crate) fn alu_add(&mut self, value: u8, carry_in: bool) {
+        let c = u8::from(carry_in && self.regs.flag_c());
+        let a = self.regs.a;
+        let result = a.wrapping_add(value).wrapping_add(c);
+        let half = (a & 0x0F) + (value & 0x0F) + c > 0x0F;
+        let carry = (a as u16) + (value as u16) + (c as u16) > 0xFF;
+        self.regs.set_flags(result == 0, false, half, carry);
+        self.regs.a = result;
+    }
+
+    pub(crate) fn alu_sub(&mut self, value: u8, carry_in: bool, store: bool) {
+        let c = u8::from(carry_in && self.regs.flag_c());
+        let a = self.regs.a;
+        let result = a.wrapping_sub(value).wrapping_sub(c);
+        let half = (a & 0x0F) < (value & 0x0F) + c;
+        let carry = (a as u16) < (value as u16) + (c as u16);
+        self.regs.set_flags(result == 0, true, half, carry);
+        if store {
+            self.regs.a = result;
+        }
+    }
+
+    pub(crate) fn alu_and(&mut self, value: u8) {
+        self.regs.a &= value;
+        self.regs.set_flags(self.regs.a == 0, false, true, false);
+    }
+
+    pub(crate) fn alu_xor(&mut self, value: u8) {
+        self.regs.a ^= value;
+        self.regs.set_flags(self.regs.a == 0, false, false, false);
+    }
+
+    pub(crate) fn alu_or(&mut self, value: u8) {
+        self.regs.a |= value;
+        self.regs.set_flags(self.regs.a == 0, false, false, false);
+    }
+
+    pub(crate) fn alu_inc(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_add(1);
+        self.regs.set_flag(FLAG_Z, result == 0);
+        self.regs.set_flag(FLAG_N, false);
+        self.regs.set_flag(FLAG_H, value & 0x0F == 0x0F);
+        result
+    }
+
+    pub(crate) fn alu_dec(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_sub(1);
+        self.regs.set_flag(FLAG_Z, result == 0);
+        self.regs.set_flag(FLAG_N, true);
+        self.regs.set_flag(FLAG_H, value & 0x0F == 0x00);
+        result
+    }
+
+    pub(crate) fn alu_add_hl(&mut self, value: u16) {
+        let hl = self.regs.hl();
+        let (result, carry) = hl.overflowing_add(value);
+        self.regs.set_flag(FLAG_N, false);
+        self.regs.set_flag(FLAG_H, (hl & 0x0FFF) + (value & 0x0FFF) > 0x0FFF);
+        self.regs.set_flag(FLAG_C, carry);
+        self.regs.set_hl(result);
+    }
+
+    /// SP plus a signed immediate, for ADD SP,e and LD HL,SP+e. H and C
+    /// come from the *unsigned* offset byte — carry out of bit 3 and bit 7
+    /// respectively — even for negative offsets. Z and N always clear.
+    pub(crate) fn alu_add_sp(&mut self, offset: i8) -> u16 {
+        let sp = self.regs.sp;
+        let byte = u16::from(offset as u8);
+        let half = (sp & 0x0F) + (byte & 0x0F) > 0x0F;
+        let carry = (sp & 0xFF) + byte > 0xFF;
+        self.regs.set_flags(false, false, half, carry);
+        sp.wrapping_add(offset as u16)
+    }
+}
@@ -0,0 +1,226 @@
+//! Arithmetic and logic instructions.
+
+use super::Opcode;
+
+macro_rules! alu_r {
+    ($t:ident, $code:expr, $mn:expr, $src:ident, $f:ident $(, $extra:expr)*) => {
+        op!($t, $code, $mn, 4, |cpu, _mmu| {
+            let value = cpu.regs.$src;
+            cpu.$f(value $(, $extra)*);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! alu_hl {
+    ($t:ident, $code:expr, $mn:expr, $f:ident $(, $extra:expr)*) => {
+        op!($t, $code, $mn, 8, |cpu, mmu| {
+            let value = mmu.read(cpu.regs.hl());
+            cpu.$f(value $(, $extra)*);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! alu_n {
+    ($t:ident, $code:expr, $mn:expr, $f:ident $(, $extra:expr)*) => {
+        op!($t, $code, $mn, 8, |cpu, mmu| {
+            let value = cpu.fetch8(mmu);
+            cpu.$f(value $(, $extra)*);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! inc_r {
+    ($t:ident, $code:expr, $mn:expr, $r:ident) => {
+        op!($t, $code, $mn, 4, |cpu, _mmu| {
+            cpu.regs.$r = cpu.alu_inc(cpu.regs.$r);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! dec_r {
+    ($t:ident, $code:expr, $mn:expr, $r:ident) => {
+        op!($t, $code, $mn, 4, |cpu, _mmu| {
+            cpu.regs.$r = cpu.alu_dec(cpu.regs.$r);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! inc_rr {
+    ($t:ident, $code:expr, $mn:expr, $get:ident, $set:ident) => {
+        op!($t, $code, $mn, 8, |cpu, _mmu| {
+            let value = cpu.regs.$get().wrapping_add(1);
+            cpu.regs.$set(value);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! dec_rr {
+    ($t:ident, $code:expr, $mn:expr, $get:ident, $set:ident) => {
+        op!($t, $code, $mn, 8, |cpu, _mmu| {
+            let value = cpu.regs.$get().wrapping_sub(1);
+            cpu.regs.$set(value);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! add_hl_rr {
+    ($t:ident, $code:expr, $mn:expr, $get:ident) => {
+        op!($t, $code, $mn, 8, |cpu, _mmu| {
+            let value = cpu.regs.$get();
+            cpu.alu_add_hl(value);
+            Ok(false)
+        });
+    };
+}
+
+#[allow(clippy::too_many_lines)]
+pub(super) fn register(t: &mut [Option<Opcode>; 256]) {
+    // ADD A,r
+    alu_r!(t, 0x80, "ADD A,B", b, alu_add, false);
+    alu_r!(t, 0x81, "ADD A,C", c, alu_add, false);
+    alu_r!(t, 0x82, "ADD A,D", d, alu_add, false);
+    alu_r!(t, 0x83, "ADD A,E", e, alu_add, false);
+    alu_r!(t, 0x84, "ADD A,H", h, alu_add, false);
+    alu_r!(t, 0x85, "ADD A,L", l, alu_add, false);
+    alu_hl!(t, 0x86, "ADD A,(HL)", alu_add, false);
+    alu_r!(t, 0x87, "ADD A,A", a, alu_add, false);
+    alu_n!(t, 0xC6, "ADD A,n", alu_add, false);
+
+    // ADC A,r
+    alu_r!(t, 0x88, "ADC A,B", b, alu_add, true);
+    alu_r!(t, 0x89, "ADC A,C", c, alu_add, true);
+    alu_r!(t, 0x8A, "ADC A,D", d, alu_add, true);
+    alu_r!(t, 0x8B, "ADC A,E", e, alu_add, true);
+    alu_r!(t, 0x8C, "ADC A,H", h, alu_add, true);
+    alu_r!(t, 0x8D, "ADC A,L", l, alu_add, true);
+    alu_hl!(t, 0x8E, "ADC A,(HL)", alu_add, true);
+    alu_r!(t, 0x8F, "ADC A,A", a, alu_add, true);
+    alu_n!(t, 0xCE, "ADC A,n", alu_add, true);
+
+    // SUB A,r
+    alu_r!(t, 0x90, "SUB A,B", b, alu_sub, false, true);
+    alu_r!(t, 0x91, "SUB A,C", c, alu_sub, false, true);
+    alu_r!(t, 0x92, "SUB A,D", d, alu_sub, false, true);
+    alu_r!(t, 0x93, "SUB A,E", e, alu_sub, false, true);
+    alu_r!(t, 0x94, "SUB A,H", h, alu_sub, false, true);
+    alu_r!(t, 0x95, "SUB A,L", l, alu_sub, false, true);
+    alu_hl!(t, 0x96, "SUB A,(HL)", alu_sub, false, true);
+    alu_r!(t, 0x97, "SUB A,A", a, alu_sub, false, true);
+    alu_n!(t, 0xD6, "SUB A,n", alu_sub, false, true);
+
+    // SBC A,r
+    alu_r!(t, 0x98, "SBC A,B", b, alu_sub, true, true);
+    alu_r!(t, 0x99, "SBC A,C", c, alu_sub, true, true);
+    alu_r!(t, 0x9A, "SBC A,D", d, alu_sub, true, true);
+    alu_r!(t, 0x9B, "SBC A,E", e, alu_sub, true, true);
+    alu_r!(t, 0x9C, "SBC A,H", h, alu_sub, true, true);
+    alu_r!(t, 0x9D, "SBC A,L", l, alu_sub, true, true);
+    alu_hl!(t, 0x9E, "SBC A,(HL)", alu_sub, true, true);
+    alu_r!(t, 0x9F, "SBC A,A", a, alu_sub, true, true);
+    alu_n!(t, 0xDE, "SBC A,n", alu_sub, true, true);
+
+    // AND / XOR / OR / CP
+    alu_r!(t, 0xA0, "AND A,B", b, alu_and);
+    alu_r!(t, 0xA1, "AND A,C", c, alu_and);
+    alu_r!(t, 0xA2, "AND A,D", d, alu_and);
+    alu_r!(t, 0xA3, "AND A,E", e, alu_and);
+    alu_r!(t, 0xA4, "AND A,H", h, alu_and);
+    alu_r!(t, 0xA5, "AND A,L", l, alu_and);
+    alu_hl!(t, 0xA6, "AND A,(HL)", alu_and);
+    alu_r!(t, 0xA7, "AND A,A", a, alu_and);
+    alu_n!(t, 0xE6, "AND A,n", alu_and);
+
+    alu_r!(t, 0xA8, "XOR A,B", b, alu_xor);
+    alu_r!(t, 0xA9, "XOR A,C", c, alu_xor);
+    alu_r!(t, 0xAA, "XOR A,D", d, alu_xor);
+    alu_r!(t, 0xAB, "XOR A,E", e, alu_xor);
+    alu_r!(t, 0xAC, "XOR A,H", h, alu_xor);
+    alu_r!(t, 0xAD, "XOR A,L", l, alu_xor);
+    alu_hl!(t, 0xAE, "XOR A,(HL)", alu_xor);
+    alu_r!(t, 0xAF, "XOR A,A", a, alu_xor);
+    alu_n!(t, 0xEE, "XOR A,n", alu_xor);
+
+    alu_r!(t, 0xB0, "OR A,B", b, alu_or);
+    alu_r!(t, 0xB1, "OR A,C", c, alu_or);
+    alu_r!(t, 0xB2, "OR A,D", d, alu_or);
+    alu_r!(t, 0xB3, "OR A,E", e, alu_or);
+    alu_r!(t, 0xB4, "OR A,H", h, alu_or);
+    alu_r!(t, 0xB5, "OR A,L", l, alu_or);
+    alu_hl!(t, 0xB6, "OR A,(HL)", alu_or);
+    alu_r!(t, 0xB7, "OR A,A", a, alu_or);
+    alu_n!(t, 0xF6, "OR A,n", alu_or);
+
+    alu_r!(t, 0xB8, "CP A,B", b, alu_sub, false, false);
+    alu_r!(t, 0xB9, "CP A,C", c, alu_sub, false, false);
+    alu_r!(t, 0xBA, "CP A,D", d, alu_sub, false, false);
+    alu_r!(t, 0xBB, "CP A,E", e, alu_sub, false, false);
+    alu_r!(t, 0xBC, "CP A,H", h, alu_sub, false, false);
+    alu_r!(t, 0xBD, "CP A,L", l, alu_sub, false, false);
+    alu_hl!(t, 0xBE, "CP A,(HL)", alu_sub, false, false);
+    alu_r!(t, 0xBF, "CP A,A", a, alu_sub, false, false);
+    alu_n!(t, 0xFE, "CP A,n", alu_sub, false, false);
+
+    // INC / DEC r
+    inc_r!(t, 0x04, "INC B", b);
+    inc_r!(t, 0x0C, "INC C", c);
+    inc_r!(t, 0x14, "INC D", d);
+    inc_r!(t, 0x1C, "INC E", e);
+    inc_r!(t, 0x24, "INC H", h);
+    inc_r!(t, 0x2C, "INC L", l);
+    inc_r!(t, 0x3C, "INC A", a);
+    dec_r!(t, 0x05, "DEC B", b);
+    dec_r!(t, 0x0D, "DEC C", c);
+    dec_r!(t, 0x15, "DEC D", d);
+    dec_r!(t, 0x1D, "DEC E", e);
+    dec_r!(t, 0x25, "DEC H", h);
+    dec_r!(t, 0x2D, "DEC L", l);
+    dec_r!(t, 0x3D, "DEC A", a);
+    op!(t, 0x34, "INC (HL)", 12, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        let value = cpu.alu_inc(mmu.read(hl));
+        mmu.write(hl, value);
+        Ok(false)
+    });
+    op!(t, 0x35, "DEC (HL)", 12, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        let value = cpu.alu_dec(mmu.read(hl));
+        mmu.write(hl, value);
+        Ok(false)
+    });
+
+    // 16-bit arithmetic
+    inc_rr!(t, 0x03, "INC BC", bc, set_bc);
+    inc_rr!(t, 0x13, "INC DE", de, set_de);
+    inc_rr!(t, 0x23, "INC HL", hl, set_hl);
+    op!(t, 0x33, "INC SP", 8, |cpu, _mmu| {
+        cpu.regs.sp = cpu.regs.sp.wrapping_add(1);
+        Ok(false)
+    });
+    dec_rr!(t, 0x0B, "DEC BC", bc, set_bc);
+    dec_rr!(t, 0x1B, "DEC DE", de, set_de);
+    dec_rr!(t, 0x2B, "DEC HL", hl, set_hl);
+    op!(t, 0x3B, "DEC SP", 8, |cpu, _mmu| {
+        cpu.regs.sp = cpu.regs.sp.wrapping_sub(1);
+        Ok(false)
+    });
+    add_hl_rr!(t, 0x09, "ADD HL,BC", bc);
+    add_hl_rr!(t, 0x19, "ADD HL,DE", de);
+    add_hl_rr!(t, 0x29, "ADD HL,HL", hl);
+    op!(t, 0x39, "ADD HL,SP", 8, |cpu, _mmu| {
+        let sp = cpu.regs.sp;
+        cpu.alu_add_hl(sp);
+        Ok(false)
+    });
+    op!(t, 0xE8, "ADD SP,e", 16, |cpu, mmu| {
+        let offset = cpu.fetch8(mmu) as i8;
+        cpu.regs.sp = cpu.alu_add_sp(offset);
+        Ok(false)
+    });
+}
@@ -0,0 +1,271 @@
+//! CB-prefixed opcodes: the full 256-entry table of rotates, shifts, SWAP
+//! and the BIT/RES/SET groups, including the `(HL)` memory forms.
+
+use super::Opcode;
+use crate::cpu::registers::{FLAG_H, FLAG_N, FLAG_Z};
+use crate::cpu::Cpu;
+use crate::mmu::Mmu;
+
+/// Operand slots in CB-opcode column order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    B,
+    C,
+    D,
+    E,
+    H,
+    L,
+    HlInd,
+    A,
+}
+
+const TARGETS: [Target; 8] = [
+    Target::B,
+    Target::C,
+    Target::D,
+    Target::E,
+    Target::H,
+    Target::L,
+    Target::HlInd,
+    Target::A,
+];
+
+impl Target {
+    fn name(self) -> &'static str {
+        match self {
+            Self::B => "B",
+            Self::C => "C",
+            Self::D => "D",
+            Self::E => "E",
+            Self::H => "H",
+            Self::L => "L",
+            Self::HlInd => "(HL)",
+            Self::A => "A",
+        }
+    }
+
+    fn get(self, cpu: &Cpu, mmu: &Mmu) -> u8 {
+        match self {
+            Self::B => cpu.regs.b,
+            Self::C => cpu.regs.c,
+            Self::D => cpu.regs.d,
+            Self::E => cpu.regs.e,
+            Self::H => cpu.regs.h,
+            Self::L => cpu.regs.l,
+            Self::HlInd => mmu.read(cpu.regs.hl()),
+            Self::A => cpu.regs.a,
+        }
+    }
+
+    fn set(self, cpu: &mut Cpu, mmu: &mut Mmu, value: u8) {
+        match self {
+            Self::B => cpu.regs.b = value,
+            Self::C => cpu.regs.c = value,
+            Self::D => cpu.regs.d = value,
+            Self::E => cpu.regs.e = value,
+            Self::H => cpu.regs.h = value,
+            Self::L => cpu.regs.l = value,
+            Self::HlInd => mmu.write(cpu.regs.hl(), value),
+            Self::A => cpu.regs.a = value,
+        }
+    }
+}
+
+fn leak(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+pub(super) fn register(t: &mut [Option<Opcode>; 256]) {
+    // BIT b,r (0x40–0x7F), RES b,r (0x80–0xBF), SET b,r (0xC0–0xFF).
+    for bit in 0..8u8 {
+        for (col, target) in TARGETS.iter().copied().enumerate() {
+            let bit_cycles = if target == Target::HlInd { 12 } else { 8 };
+            let rw_cycles = if target == Target::HlInd { 16 } else { 8 };
+
+            t[0x40 + bit as usize * 8 + col] = Some(Opcode {
+                mnemonic: leak(format!("BIT {bit},{}", target.name())),
+                base_cycles: bit_cycles,
+                conditional_cycles: 0,
+                exec: Box::new(move |cpu, mmu| {
+                    let value = target.get(cpu, mmu);
+                    cpu.regs.set_flag(FLAG_Z, value & (1 << bit) == 0);
+                    cpu.regs.set_flag(FLAG_N, false);
+                    cpu.regs.set_flag(FLAG_H, true);
+                    Ok(false)
+                }),
+            });
+            t[0x80 + bit as usize * 8 + col] = Some(Opcode {
+                mnemonic: leak(format!("RES {bit},{}", target.name())),
+                base_cycles: rw_cycles,
+                conditional_cycles: 0,
+                exec: Box::new(move |cpu, mmu| {
+                    let value = target.get(cpu, mmu) & !(1 << bit);
+                    target.set(cpu, mmu, value);
+                    Ok(false)
+                }),
+            });
+            t[0xC0 + bit as usize * 8 + col] = Some(Opcode {
+                mnemonic: leak(format!("SET {bit},{}", target.name())),
+                base_cycles: rw_cycles,
+                conditional_cycles: 0,
+                exec: Box::new(move |cpu, mmu| {
+                    let value = target.get(cpu, mmu) | (1 << bit);
+                    target.set(cpu, mmu, value);
+                    Ok(false)
+                }),
+            });
+        }
+    }
+
+    // Rotate/shift group (0x00–0x3F) for every target including (HL).
+    for (col, target) in TARGETS.iter().copied().enumerate() {
+        rot_op(t, col, target, 0x00, "RLC", |cpu, v| {
+            let carry = v & 0x80 != 0;
+            let out = v.rotate_left(1);
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x08, "RRC", |cpu, v| {
+            let carry = v & 0x01 != 0;
+            let out = v.rotate_right(1);
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x10, "RL", |cpu, v| {
+            let carry = v & 0x80 != 0;
+            let out = (v << 1) | u8::from(cpu.regs.flag_c());
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x18, "RR", |cpu, v| {
+            let carry = v & 0x01 != 0;
+            let out = (v >> 1) | (u8::from(cpu.regs.flag_c()) << 7);
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x20, "SLA", |cpu, v| {
+            let carry = v & 0x80 != 0;
+            let out = v << 1;
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x28, "SRA", |cpu, v| {
+            let carry = v & 0x01 != 0;
+            let out = (v >> 1) | (v & 0x80);
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+        rot_op(t, col, target, 0x30, "SWAP", |cpu, v| {
+            let out = v.rotate_left(4);
+            set_rot_flags(cpu, out, false);
+            out
+        });
+        rot_op(t, col, target, 0x38, "SRL", |cpu, v| {
+            let carry = v & 0x01 != 0;
+            let out = v >> 1;
+            set_rot_flags(cpu, out, carry);
+            out
+        });
+    }
+}
+
+fn set_rot_flags(cpu: &mut Cpu, result: u8, carry: bool) {
+    cpu.regs.set_flags(result == 0, false, false, carry);
+}
+
+fn rot_op(
+    t: &mut [Option<Opcode>; 256],
+    col: usize,
+    target: Target,
+    row: usize,
+    name: &str,
+    f: fn(&mut Cpu, u8) -> u8,
+) {
+    t[row + col] = Some(Opcode {
+        mnemonic: leak(format!("{name} {}", target.name())),
+        base_cycles: if target == Target::HlInd { 16 } else { 8 },
+        conditional_cycles: 0,
+        exec: Box::new(move |cpu, mmu| {
+            let value = target.get(cpu, mmu);
+            let result = f(cpu, value);
+            target.set(cpu, mmu, result);
+            Ok(false)
+        }),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::cb_opcode;
+    use crate::cartridge::Cartridge;
+    use crate::cpu::Cpu;
+    use crate::mmu::Mmu;
+
+    fn harness() -> (Cpu, Mmu) {
+        let rom = vec![0u8; 0x8000];
+        (Cpu::new(), Mmu::new(Cartridge::new(rom).unwrap()))
+    }
+
+    /// Run one CB opcode with register B (column 0) preloaded.
+    fn exec_on_b(code: u8, input: u8, carry_in: bool) -> (u8, u8) {
+        let (mut cpu, mut mmu) = harness();
+        cpu.regs.b = input;
+        cpu.regs.set_flag(crate::cpu::registers::FLAG_C, carry_in);
+        let op = cb_opcode(code).expect("mapped");
+        (op.exec)(&mut cpu, &mut mmu).unwrap();
+        (cpu.regs.b, cpu.regs.f)
+    }
+
+    #[test]
+    fn all_256_cb_opcodes_are_mapped() {
+        for byte in 0..=0xFFu8 {
+            assert!(cb_opcode(byte).is_some(), "CB {byte:#04X} should be mapped");
+        }
+    }
+
+    #[test]
+    fn rotate_shift_table_driven_flags() {
+        // (opcode on B, input, carry-in, expected output, expected F)
+        let cases: &[(u8, u8, bool, u8, u8)] = &[
+            (0x00, 0x85, false, 0x0B, 0x10), // RLC
+            (0x00, 0x00, false, 0x00, 0x80), // RLC zero
+            (0x08, 0x01, false, 0x80, 0x10), // RRC
+            (0x10, 0x85, false, 0x0A, 0x10), // RL, carry-in 0
+            (0x10, 0x80, true, 0x01, 0x10),  // RL, carry-in 1
+            (0x18, 0x01, false, 0x00, 0x90), // RR into zero with carry-out
+            (0x20, 0x80, false, 0x00, 0x90), // SLA
+            (0x28, 0x81, false, 0xC0, 0x10), // SRA keeps bit 7
+            (0x30, 0xF0, true, 0x0F, 0x00),  // SWAP clears C
+            (0x30, 0x00, true, 0x00, 0x80),  // SWAP zero
+            (0x38, 0x81, false, 0x40, 0x10), // SRL
+        ];
+        for &(code, input, carry_in, out, flags) in cases {
+            let (result, f) = exec_on_b(code, input, carry_in);
+            assert_eq!(result, out, "CB {code:#04X} on {input:#04X}");
+            assert_eq!(f, flags, "flags for CB {code:#04X} on {input:#04X}");
+        }
+    }
+
+    #[test]
+    fn hl_forms_read_and_write_through_the_bus() {
+        let (mut cpu, mut mmu) = harness();
+        cpu.regs.set_hl(0xC000);
+        mmu.write(0xC000, 0xF0);
+        let op = cb_opcode(0x36).expect("SWAP (HL)"); // column 6
+        assert_eq!(op.base_cycles, 16);
+        (op.exec)(&mut cpu, &mut mmu).unwrap();
+        assert_eq!(mmu.read(0xC000), 0x0F);
+    }
+
+    #[test]
+    fn bit_set_res_behave() {
+        let (mut cpu, mut mmu) = harness();
+        cpu.regs.b = 0b0100_0000;
+        (cb_opcode(0x70).unwrap().exec)(&mut cpu, &mut mmu).unwrap(); // BIT 6,B
+        assert!(!cpu.regs.flag_z());
+        (cb_opcode(0xB0).unwrap().exec)(&mut cpu, &mut mmu).unwrap(); // RES 6,B
+        assert_eq!(cpu.regs.b, 0);
+        (cb_opcode(0xC0).unwrap().exec)(&mut cpu, &mut mmu).unwrap(); // SET 0,B
+        assert_eq!(cpu.regs.b, 1);
+    }
+}
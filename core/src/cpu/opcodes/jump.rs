@@ -0,0 +1,74 @@
+//! Jumps, calls, returns and restarts.
+
+use super::Opcode;
+use crate::cpu::Cpu;
+
+macro_rules! rst {
+    ($t:ident, $code:expr, $mn:expr, $target:expr) => {
+        op!($t, $code, $mn, 16, |cpu, mmu| {
+            cpu.push16(mmu, cpu.regs.pc);
+            cpu.regs.pc = $target;
+            Ok(false)
+        });
+    };
+}
+
+pub(super) fn register(t: &mut [Option<Opcode>; 256]) {
+    op!(t, 0xC3, "JP nn", 16, |cpu, mmu| {
+        cpu.regs.pc = cpu.fetch16(mmu);
+        Ok(false)
+    });
+    op!(t, 0xE9, "JP HL", 4, |cpu, _mmu| {
+        cpu.regs.pc = cpu.regs.hl();
+        Ok(false)
+    });
+    op!(t, 0x18, "JR e", 12, |cpu, mmu| {
+        let offset = cpu.fetch8(mmu) as i8;
+        cpu.regs.pc = cpu.regs.pc.wrapping_add(offset as u16);
+        Ok(false)
+    });
+
+    jr_cc_e!(t, 0x20, "JR NZ,e", |cpu: &Cpu| !cpu.regs.flag_z());
+    jr_cc_e!(t, 0x28, "JR Z,e", |cpu: &Cpu| cpu.regs.flag_z());
+    jr_cc_e!(t, 0x30, "JR NC,e", |cpu: &Cpu| !cpu.regs.flag_c());
+    jr_cc_e!(t, 0x38, "JR C,e", |cpu: &Cpu| cpu.regs.flag_c());
+
+    jp_cc_nn!(t, 0xC2, "JP NZ,nn", |cpu: &Cpu| !cpu.regs.flag_z());
+    jp_cc_nn!(t, 0xCA, "JP Z,nn", |cpu: &Cpu| cpu.regs.flag_z());
+    jp_cc_nn!(t, 0xD2, "JP NC,nn", |cpu: &Cpu| !cpu.regs.flag_c());
+    jp_cc_nn!(t, 0xDA, "JP C,nn", |cpu: &Cpu| cpu.regs.flag_c());
+
+    op!(t, 0xCD, "CALL nn", 24, |cpu, mmu| {
+        let target = cpu.fetch16(mmu);
+        cpu.push16(mmu, cpu.regs.pc);
+        cpu.regs.pc = target;
+        Ok(false)
+    });
+    call_cc_nn!(t, 0xC4, "CALL NZ,nn", |cpu: &Cpu| !cpu.regs.flag_z());
+    call_cc_nn!(t, 0xCC, "CALL Z,nn", |cpu: &Cpu| cpu.regs.flag_z());
+    call_cc_nn!(t, 0xD4, "CALL NC,nn", |cpu: &Cpu| !cpu.regs.flag_c());
+    call_cc_nn!(t, 0xDC, "CALL C,nn", |cpu: &Cpu| cpu.regs.flag_c());
+
+    op!(t, 0xC9, "RET", 16, |cpu, mmu| {
+        cpu.regs.pc = cpu.pop16(mmu);
+        Ok(false)
+    });
+    op!(t, 0xD9, "RETI", 16, |cpu, mmu| {
+        cpu.regs.pc = cpu.pop16(mmu);
+        cpu.enable_ime();
+        Ok(false)
+    });
+    ret_cc!(t, 0xC0, "RET NZ", |cpu: &Cpu| !cpu.regs.flag_z());
+    ret_cc!(t, 0xC8, "RET Z", |cpu: &Cpu| cpu.regs.flag_z());
+    ret_cc!(t, 0xD0, "RET NC", |cpu: &Cpu| !cpu.regs.flag_c());
+    ret_cc!(t, 0xD8, "RET C", |cpu: &Cpu| cpu.regs.flag_c());
+
+    rst!(t, 0xC7, "RST 00", 0x0000);
+    rst!(t, 0xCF, "RST 08", 0x0008);
+    rst!(t, 0xD7, "RST 10", 0x0010);
+    rst!(t, 0xDF, "RST 18", 0x0018);
+    rst!(t, 0xE7, "RST 20", 0x0020);
+    rst!(t, 0xEF, "RST 28", 0x0028);
+    rst!(t, 0xF7, "RST 30", 0x0030);
+    rst!(t, 0xFF, "RST 38", 0x0038);
+}
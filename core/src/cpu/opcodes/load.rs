@@ -0,0 +1,265 @@
+//! 8-bit and 16-bit load instructions.
+
+use super::Opcode;
+
+macro_rules! ld_r_r {
+    ($t:ident, $code:expr, $mn:expr, $dst:ident, $src:ident) => {
+        op!($t, $code, $mn, 4, |cpu, _mmu| {
+            // LD r,r with identical operands is an architectural no-op.
+            #[allow(clippy::self_assignment)]
+            {
+                cpu.regs.$dst = cpu.regs.$src;
+            }
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! ld_r_n {
+    ($t:ident, $code:expr, $mn:expr, $dst:ident) => {
+        op!($t, $code, $mn, 8, |cpu, mmu| {
+            cpu.regs.$dst = cpu.fetch8(mmu);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! ld_r_hl {
+    ($t:ident, $code:expr, $mn:expr, $dst:ident) => {
+        op!($t, $code, $mn, 8, |cpu, mmu| {
+            cpu.regs.$dst = mmu.read(cpu.regs.hl());
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! ld_hl_r {
+    ($t:ident, $code:expr, $mn:expr, $src:ident) => {
+        op!($t, $code, $mn, 8, |cpu, mmu| {
+            mmu.write(cpu.regs.hl(), cpu.regs.$src);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! ld_rr_nn {
+    ($t:ident, $code:expr, $mn:expr, $set:ident) => {
+        op!($t, $code, $mn, 12, |cpu, mmu| {
+            let value = cpu.fetch16(mmu);
+            cpu.regs.$set(value);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! push_rr {
+    ($t:ident, $code:expr, $mn:expr, $get:ident) => {
+        op!($t, $code, $mn, 16, |cpu, mmu| {
+            let value = cpu.regs.$get();
+            cpu.push16(mmu, value);
+            Ok(false)
+        });
+    };
+}
+
+macro_rules! pop_rr {
+    ($t:ident, $code:expr, $mn:expr, $set:ident) => {
+        op!($t, $code, $mn, 12, |cpu, mmu| {
+            let value = cpu.pop16(mmu);
+            cpu.regs.$set(value);
+            Ok(false)
+        });
+    };
+}
+
+#[allow(clippy::too_many_lines)]
+pub(super) fn register(t: &mut [Option<Opcode>; 256]) {
+    // LD r,r'
+    ld_r_r!(t, 0x40, "LD B,B", b, b);
+    ld_r_r!(t, 0x41, "LD B,C", b, c);
+    ld_r_r!(t, 0x42, "LD B,D", b, d);
+    ld_r_r!(t, 0x43, "LD B,E", b, e);
+    ld_r_r!(t, 0x44, "LD B,H", b, h);
+    ld_r_r!(t, 0x45, "LD B,L", b, l);
+    ld_r_r!(t, 0x47, "LD B,A", b, a);
+    ld_r_r!(t, 0x48, "LD C,B", c, b);
+    ld_r_r!(t, 0x49, "LD C,C", c, c);
+    ld_r_r!(t, 0x4A, "LD C,D", c, d);
+    ld_r_r!(t, 0x4B, "LD C,E", c, e);
+    ld_r_r!(t, 0x4C, "LD C,H", c, h);
+    ld_r_r!(t, 0x4D, "LD C,L", c, l);
+    ld_r_r!(t, 0x4F, "LD C,A", c, a);
+    ld_r_r!(t, 0x50, "LD D,B", d, b);
+    ld_r_r!(t, 0x51, "LD D,C", d, c);
+    ld_r_r!(t, 0x52, "LD D,D", d, d);
+    ld_r_r!(t, 0x53, "LD D,E", d, e);
+    ld_r_r!(t, 0x54, "LD D,H", d, h);
+    ld_r_r!(t, 0x55, "LD D,L", d, l);
+    ld_r_r!(t, 0x57, "LD D,A", d, a);
+    ld_r_r!(t, 0x58, "LD E,B", e, b);
+    ld_r_r!(t, 0x59, "LD E,C", e, c);
+    ld_r_r!(t, 0x5A, "LD E,D", e, d);
+    ld_r_r!(t, 0x5B, "LD E,E", e, e);
+    ld_r_r!(t, 0x5C, "LD E,H", e, h);
+    ld_r_r!(t, 0x5D, "LD E,L", e, l);
+    ld_r_r!(t, 0x5F, "LD E,A", e, a);
+    ld_r_r!(t, 0x60, "LD H,B", h, b);
+    ld_r_r!(t, 0x61, "LD H,C", h, c);
+    ld_r_r!(t, 0x62, "LD H,D", h, d);
+    ld_r_r!(t, 0x63, "LD H,E", h, e);
+    ld_r_r!(t, 0x64, "LD H,H", h, h);
+    ld_r_r!(t, 0x65, "LD H,L", h, l);
+    ld_r_r!(t, 0x67, "LD H,A", h, a);
+    ld_r_r!(t, 0x68, "LD L,B", l, b);
+    ld_r_r!(t, 0x69, "LD L,C", l, c);
+    ld_r_r!(t, 0x6A, "LD L,D", l, d);
+    ld_r_r!(t, 0x6B, "LD L,E", l, e);
+    ld_r_r!(t, 0x6C, "LD L,H", l, h);
+    ld_r_r!(t, 0x6D, "LD L,L", l, l);
+    ld_r_r!(t, 0x6F, "LD L,A", l, a);
+    ld_r_r!(t, 0x78, "LD A,B", a, b);
+    ld_r_r!(t, 0x79, "LD A,C", a, c);
+    ld_r_r!(t, 0x7A, "LD A,D", a, d);
+    ld_r_r!(t, 0x7B, "LD A,E", a, e);
+    ld_r_r!(t, 0x7C, "LD A,H", a, h);
+    ld_r_r!(t, 0x7D, "LD A,L", a, l);
+    ld_r_r!(t, 0x7F, "LD A,A", a, a);
+
+    // LD r,n
+    ld_r_n!(t, 0x06, "LD B,n", b);
+    ld_r_n!(t, 0x0E, "LD C,n", c);
+    ld_r_n!(t, 0x16, "LD D,n", d);
+    ld_r_n!(t, 0x1E, "LD E,n", e);
+    ld_r_n!(t, 0x26, "LD H,n", h);
+    ld_r_n!(t, 0x2E, "LD L,n", l);
+    ld_r_n!(t, 0x3E, "LD A,n", a);
+
+    // LD r,(HL) / LD (HL),r
+    ld_r_hl!(t, 0x46, "LD B,(HL)", b);
+    ld_r_hl!(t, 0x4E, "LD C,(HL)", c);
+    ld_r_hl!(t, 0x56, "LD D,(HL)", d);
+    ld_r_hl!(t, 0x5E, "LD E,(HL)", e);
+    ld_r_hl!(t, 0x66, "LD H,(HL)", h);
+    ld_r_hl!(t, 0x6E, "LD L,(HL)", l);
+    ld_r_hl!(t, 0x7E, "LD A,(HL)", a);
+    ld_hl_r!(t, 0x70, "LD (HL),B", b);
+    ld_hl_r!(t, 0x71, "LD (HL),C", c);
+    ld_hl_r!(t, 0x72, "LD (HL),D", d);
+    ld_hl_r!(t, 0x73, "LD (HL),E", e);
+    ld_hl_r!(t, 0x74, "LD (HL),H", h);
+    ld_hl_r!(t, 0x75, "LD (HL),L", l);
+    ld_hl_r!(t, 0x77, "LD (HL),A", a);
+    op!(t, 0x36, "LD (HL),n", 12, |cpu, mmu| {
+        let value = cpu.fetch8(mmu);
+        mmu.write(cpu.regs.hl(), value);
+        Ok(false)
+    });
+
+    // Indirect A loads
+    op!(t, 0x02, "LD (BC),A", 8, |cpu, mmu| {
+        mmu.write(cpu.regs.bc(), cpu.regs.a);
+        Ok(false)
+    });
+    op!(t, 0x12, "LD (DE),A", 8, |cpu, mmu| {
+        mmu.write(cpu.regs.de(), cpu.regs.a);
+        Ok(false)
+    });
+    op!(t, 0x0A, "LD A,(BC)", 8, |cpu, mmu| {
+        cpu.regs.a = mmu.read(cpu.regs.bc());
+        Ok(false)
+    });
+    op!(t, 0x1A, "LD A,(DE)", 8, |cpu, mmu| {
+        cpu.regs.a = mmu.read(cpu.regs.de());
+        Ok(false)
+    });
+    op!(t, 0x22, "LD (HL+),A", 8, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        mmu.write(hl, cpu.regs.a);
+        cpu.regs.set_hl(hl.wrapping_add(1));
+        Ok(false)
+    });
+    op!(t, 0x2A, "LD A,(HL+)", 8, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        cpu.regs.a = mmu.read(hl);
+        cpu.regs.set_hl(hl.wrapping_add(1));
+        Ok(false)
+    });
+    op!(t, 0x32, "LD (HL-),A", 8, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        mmu.write(hl, cpu.regs.a);
+        cpu.regs.set_hl(hl.wrapping_sub(1));
+        Ok(false)
+    });
+    op!(t, 0x3A, "LD A,(HL-)", 8, |cpu, mmu| {
+        let hl = cpu.regs.hl();
+        cpu.regs.a = mmu.read(hl);
+        cpu.regs.set_hl(hl.wrapping_sub(1));
+        Ok(false)
+    });
+    op!(t, 0xEA, "LD (nn),A", 16, |cpu, mmu| {
+        let addr = cpu.fetch16(mmu);
+        mmu.write(addr, cpu.regs.a);
+        Ok(false)
+    });
+    op!(t, 0xFA, "LD A,(nn)", 16, |cpu, mmu| {
+        let addr = cpu.fetch16(mmu);
+        cpu.regs.a = mmu.read(addr);
+        Ok(false)
+    });
+
+    // High-RAM loads
+    op!(t, 0xE0, "LDH (n),A", 12, |cpu, mmu| {
+        let offset = cpu.fetch8(mmu);
+        mmu.write(0xFF00 | offset as u16, cpu.regs.a);
+        Ok(false)
+    });
+    op!(t, 0xF0, "LDH A,(n)", 12, |cpu, mmu| {
+        let offset = cpu.fetch8(mmu);
+        cpu.regs.a = mmu.read(0xFF00 | offset as u16);
+        Ok(false)
+    });
+    op!(t, 0xE2, "LD (C),A", 8, |cpu, mmu| {
+        mmu.write(0xFF00 | cpu.regs.c as u16, cpu.regs.a);
+        Ok(false)
+    });
+    op!(t, 0xF2, "LD A,(C)", 8, |cpu, mmu| {
+        cpu.regs.a = mmu.read(0xFF00 | cpu.regs.c as u16);
+        Ok(false)
+    });
+
+    // 16-bit loads
+    ld_rr_nn!(t, 0x01, "LD BC,nn", set_bc);
+    ld_rr_nn!(t, 0x11, "LD DE,nn", set_de);
+    ld_rr_nn!(t, 0x21, "LD HL,nn", set_hl);
+    op!(t, 0x31, "LD SP,nn", 12, |cpu, mmu| {
+        cpu.regs.sp = cpu.fetch16(mmu);
+        Ok(false)
+    });
+    op!(t, 0x08, "LD (nn),SP", 20, |cpu, mmu| {
+        let addr = cpu.fetch16(mmu);
+        let [hi, lo] = cpu.regs.sp.to_be_bytes();
+        mmu.write(addr, lo);
+        mmu.write(addr.wrapping_add(1), hi);
+        Ok(false)
+    });
+    op!(t, 0xF9, "LD SP,HL", 8, |cpu, _mmu| {
+        cpu.regs.sp = cpu.regs.hl();
+        Ok(false)
+    });
+    op!(t, 0xF8, "LD HL,SP+e", 12, |cpu, mmu| {
+        let offset = cpu.fetch8(mmu) as i8;
+        let value = cpu.alu_add_sp(offset);
+        cpu.regs.set_hl(value);
+        Ok(false)
+    });
+
+    // Stack push/pop
+    push_rr!(t, 0xC5, "PUSH BC", bc);
+    push_rr!(t, 0xD5, "PUSH DE", de);
+    push_rr!(t, 0xE5, "PUSH HL", hl);
+    push_rr!(t, 0xF5, "PUSH AF", af);
+    pop_rr!(t, 0xC1, "POP BC", set_bc);
+    pop_rr!(t, 0xD1, "POP DE", set_de);
+    pop_rr!(t, 0xE1, "POP HL", set_hl);
+    pop_rr!(t, 0xF1, "POP AF", set_af);
+}
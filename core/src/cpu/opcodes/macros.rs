@@ -0,0 +1,93 @@
+//! Helper macros for filling the opcode table.
+
+/// Register a simple opcode with a fixed cycle cost.
+macro_rules! op {
+    ($t:ident, $code:expr, $mn:expr, $cycles:expr, $exec:expr) => {
+        $t[$code as usize] = Some(Opcode {
+            mnemonic: $mn,
+            base_cycles: $cycles,
+            conditional_cycles: 0,
+            exec: Box::new($exec),
+        });
+    };
+}
+
+/// JR cc,e — relative jump when the condition holds. 8 cycles, plus 4 when
+/// the branch is taken.
+macro_rules! jr_cc_e {
+    ($t:ident, $code:expr, $mn:expr, $cond:expr) => {
+        $t[$code as usize] = Some(Opcode {
+            mnemonic: $mn,
+            base_cycles: 8,
+            conditional_cycles: 4,
+            exec: Box::new(|cpu, mmu| {
+                let offset = cpu.fetch8(mmu) as i8;
+                if ($cond)(cpu) {
+                    cpu.regs.pc = cpu.regs.pc.wrapping_add(offset as u16);
+                    return Ok(true);
+                }
+                Ok(false)
+            }),
+        });
+    };
+}
+
+/// JP cc,nn — absolute jump when the condition holds. 12 cycles, plus 4 when
+/// the branch is taken.
+macro_rules! jp_cc_nn {
+    ($t:ident, $code:expr, $mn:expr, $cond:expr) => {
+        $t[$code as usize] = Some(Opcode {
+            mnemonic: $mn,
+            base_cycles: 12,
+            conditional_cycles: 4,
+            exec: Box::new(|cpu, mmu| {
+                let target = cpu.fetch16(mmu);
+                if ($cond)(cpu) {
+                    cpu.regs.pc = target;
+                    return Ok(true);
+                }
+                Ok(false)
+            }),
+        });
+    };
+}
+
+/// CALL cc,nn — push PC and jump when the condition holds. 12 cycles, plus
+/// 12 when the branch is taken.
+macro_rules! call_cc_nn {
+    ($t:ident, $code:expr, $mn:expr, $cond:expr) => {
+        $t[$code as usize] = Some(Opcode {
+            mnemonic: $mn,
+            base_cycles: 12,
+            conditional_cycles: 12,
+            exec: Box::new(|cpu, mmu| {
+                let target = cpu.fetch16(mmu);
+                if ($cond)(cpu) {
+                    cpu.push16(mmu, cpu.regs.pc);
+                    cpu.regs.pc = target;
+                    return Ok(true);
+                }
+                Ok(false)
+            }),
+        });
+    };
+}
+
+/// RET cc — pop the return address when the condition holds. 8 cycles, plus
+/// 12 when the branch is taken.
+macro_rules! ret_cc {
+    ($t:ident, $code:expr, $mn:expr, $cond:expr) => {
+        $t[$code as usize] = Some(Opcode {
+            mnemonic: $mn,
+            base_cycles: 8,
+            conditional_cycles: 12,
+            exec: Box::new(|cpu, mmu| {
+                if ($cond)(cpu) {
+                    cpu.regs.pc = cpu.pop16(mmu);
+                    return Ok(true);
+                }
+                Ok(false)
+            }),
+        });
+    };
+}
@@ -0,0 +1,115 @@
+//! Control, flag and rotate instructions, plus the CB prefix stub.
+
+use super::Opcode;
+use crate::cpu::registers::{FLAG_C, FLAG_H, FLAG_N, FLAG_Z};
+
+pub(super) fn register(t: &mut [Option<Opcode>; 256]) {
+    op!(t, 0x00, "NOP", 4, |_cpu, _mmu| Ok(false));
+    // STOP. On DMG the machine (CPU, timer, PPU, APU) stalls until a
+    // joypad line goes low; `Cpu::check_stop_wake` handles the resume. On
+    // CGB, STOP with KEY1 (0xFF4D) bit 0 armed performs a double-speed
+    // switch instead of stalling — unimplemented here, as KEY1 is not
+    // modelled; reads of 0xFF4D return open bus like the other CGB-only
+    // registers.
+    op!(t, 0x10, "STOP", 4, |cpu, mmu| {
+        // STOP has a one-byte padding operand.
+        let _ = cpu.fetch8(mmu);
+        // With an enabled interrupt already pending, STOP falls through
+        // immediately; otherwise the whole machine stalls until a button
+        // press pulls a selected joypad line low.
+        cpu.stopped = mmu.interrupt_flags() & mmu.interrupt_enable() & 0x1F == 0;
+        // Entering STOP resets the divider on hardware.
+        mmu.timer.reset_div();
+        Ok(false)
+    });
+    op!(t, 0x76, "HALT", 4, |cpu, _mmu| {
+        cpu.halted = true;
+        Ok(false)
+    });
+    op!(t, 0xF3, "DI", 4, |cpu, _mmu| {
+        cpu.disable_ime();
+        Ok(false)
+    });
+    op!(t, 0xFB, "EI", 4, |cpu, _mmu| {
+        // IME turns on only after the instruction following EI.
+        cpu.schedule_enable_ime();
+        Ok(false)
+    });
+
+    op!(t, 0x2F, "CPL", 4, |cpu, _mmu| {
+        cpu.regs.a = !cpu.regs.a;
+        cpu.regs.set_flag(FLAG_N, true);
+        cpu.regs.set_flag(FLAG_H, true);
+        Ok(false)
+    });
+    op!(t, 0x37, "SCF", 4, |cpu, _mmu| {
+        cpu.regs.set_flag(FLAG_N, false);
+        cpu.regs.set_flag(FLAG_H, false);
+        cpu.regs.set_flag(FLAG_C, true);
+        Ok(false)
+    });
+    op!(t, 0x3F, "CCF", 4, |cpu, _mmu| {
+        let carry = cpu.regs.flag_c();
+        cpu.regs.set_flag(FLAG_N, false);
+        cpu.regs.set_flag(FLAG_H, false);
+        cpu.regs.set_flag(FLAG_C, !carry);
+        Ok(false)
+    });
+    op!(t, 0x27, "DAA", 4, |cpu, _mmu| {
+        // Decimal-adjust A after an addition (N clear) or subtraction
+        // (N set): N picks the direction, H/C record the binary carries.
+        let mut a = cpu.regs.a;
+        let mut carry = cpu.regs.flag_c();
+        if cpu.regs.flag_n() {
+            let mut adjust = 0u8;
+            if cpu.regs.flag_h() {
+                adjust |= 0x06;
+            }
+            if carry {
+                adjust |= 0x60;
+            }
+            a = a.wrapping_sub(adjust);
+        } else {
+            let mut adjust = 0u8;
+            if cpu.regs.flag_h() || a & 0x0F > 0x09 {
+                adjust |= 0x06;
+            }
+            if carry || a > 0x99 {
+                adjust |= 0x60;
+                carry = true;
+            }
+            a = a.wrapping_add(adjust);
+        }
+        cpu.regs.a = a;
+        cpu.regs.set_flag(FLAG_Z, a == 0);
+        cpu.regs.set_flag(FLAG_H, false);
+        cpu.regs.set_flag(FLAG_C, carry);
+        Ok(false)
+    });
+
+    // Accumulator rotates (always clear Z, unlike their CB cousins).
+    op!(t, 0x07, "RLCA", 4, |cpu, _mmu| {
+        let carry = cpu.regs.a & 0x80 != 0;
+        cpu.regs.a = cpu.regs.a.rotate_left(1);
+        cpu.regs.set_flags(false, false, false, carry);
+        Ok(false)
+    });
+    op!(t, 0x0F, "RRCA", 4, |cpu, _mmu| {
+        let carry = cpu.regs.a & 0x01 != 0;
+        cpu.regs.a = cpu.regs.a.rotate_right(1);
+        cpu.regs.set_flags(false, false, false, carry);
+        Ok(false)
+    });
+    op!(t, 0x17, "RLA", 4, |cpu, _mmu| {
+        let carry = cpu.regs.a & 0x80 != 0;
+        cpu.regs.a = (cpu.regs.a << 1) | u8::from(cpu.regs.flag_c());
+        cpu.regs.set_flags(false, false, false, carry);
+        Ok(false)
+    });
+    op!(t, 0x1F, "RRA", 4, |cpu, _mmu| {
+        let carry = cpu.regs.a & 0x01 != 0;
+        cpu.regs.a = (cpu.regs.a >> 1) | (u8::from(cpu.regs.flag_c()) << 7);
+        cpu.regs.set_flags(false, false, false, carry);
+        Ok(false)
+    });
+}
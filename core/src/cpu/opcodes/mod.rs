@@ -0,0 +1,84 @@
+//! Opcode table for the base (non-CB) instruction set.
+//!
+//! Each entry carries its mnemonic, base cycle cost and an `exec` function.
+//! `exec` returns `true` when the instruction took a conditional branch, in
+//! which case [`Opcode::conditional_cycles`] is added on top of the base cost.
+
+#[macro_use]
+mod macros;
+
+mod alu;
+mod cb;
+mod jump;
+mod load;
+mod misc;
+
+use std::sync::OnceLock;
+
+use crate::cpu::{Cpu, CpuError};
+use crate::mmu::Mmu;
+
+pub type ExecFn = Box<dyn Fn(&mut Cpu, &mut Mmu) -> Result<bool, CpuError> + Send + Sync>;
+
+/// A decoded instruction slot in the opcode table.
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub base_cycles: u8,
+    pub conditional_cycles: u8,
+    pub exec: ExecFn,
+}
+
+static OPCODES: OnceLock<[Option<Opcode>; 256]> = OnceLock::new();
+static CB_OPCODES: OnceLock<[Option<Opcode>; 256]> = OnceLock::new();
+
+/// Look up the table entry for `byte`, or `None` for illegal opcodes.
+#[must_use]
+pub fn opcode(byte: u8) -> Option<&'static Opcode> {
+    table()[byte as usize].as_ref()
+}
+
+/// Look up the CB-prefixed entry for `byte`.
+#[must_use]
+pub fn cb_opcode(byte: u8) -> Option<&'static Opcode> {
+    CB_OPCODES
+        .get_or_init(|| {
+            let mut t: [Option<Opcode>; 256] = std::array::from_fn(|_| None);
+            cb::register(&mut t);
+            t
+        })[byte as usize]
+        .as_ref()
+}
+
+fn table() -> &'static [Option<Opcode>; 256] {
+    OPCODES.get_or_init(|| {
+        let mut t: [Option<Opcode>; 256] = std::array::from_fn(|_| None);
+        misc::register(&mut t);
+        load::register(&mut t);
+        alu::register(&mut t);
+        jump::register(&mut t);
+        t
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn illegal_opcodes_are_unmapped() {
+        for byte in [0xD3u8, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD] {
+            assert!(opcode(byte).is_none(), "{byte:#04X} should be illegal");
+        }
+    }
+
+    #[test]
+    fn all_defined_opcodes_are_mapped() {
+        let illegal = [0xD3u8, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD];
+        for byte in 0..=0xFFu8 {
+            // 0xCB is the prefix byte, dispatched separately in Cpu::step.
+            if byte != 0xCB && !illegal.contains(&byte) {
+                assert!(opcode(byte).is_some(), "{byte:#04X} should be mapped");
+            }
+        }
+    }
+}
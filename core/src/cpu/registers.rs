@@ -0,0 +1,133 @@
+//! CPU register file and flag helpers.
+
+/// Zero flag bit in F.
+pub const FLAG_Z: u8 = 0x80;
+/// Subtract flag bit in F.
+pub const FLAG_N: u8 = 0x40;
+/// Half-carry flag bit in F.
+pub const FLAG_H: u8 = 0x20;
+/// Carry flag bit in F.
+pub const FLAG_C: u8 = 0x10;
+
+/// The SM83 register file.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Registers {
+    pub a: u8,
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16,
+}
+
+impl Registers {
+    #[must_use]
+    pub fn af(&self) -> u16 {
+        u16::from_be_bytes([self.a, self.f])
+    }
+
+    #[must_use]
+    pub fn bc(&self) -> u16 {
+        u16::from_be_bytes([self.b, self.c])
+    }
+
+    #[must_use]
+    pub fn de(&self) -> u16 {
+        u16::from_be_bytes([self.d, self.e])
+    }
+
+    #[must_use]
+    pub fn hl(&self) -> u16 {
+        u16::from_be_bytes([self.h, self.l])
+    }
+
+    pub fn set_af(&mut self, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.a = hi;
+        // The low nibble of F does not physically exist.
+        self.f = lo & 0xF0;
+    }
+
+    pub fn set_bc(&mut self, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.b = hi;
+        self.c = lo;
+    }
+
+    pub fn set_de(&mut self, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.d = hi;
+        self.e = lo;
+    }
+
+    pub fn set_hl(&mut self, value: u16) {
+        let [hi, lo] = value.to_be_bytes();
+        self.h = hi;
+        self.l = lo;
+    }
+
+    #[must_use]
+    pub fn flag_z(&self) -> bool {
+        self.f & FLAG_Z != 0
+    }
+
+    #[must_use]
+    pub fn flag_n(&self) -> bool {
+        self.f & FLAG_N != 0
+    }
+
+    #[must_use]
+    pub fn flag_h(&self) -> bool {
+        self.f & FLAG_H != 0
+    }
+
+    #[must_use]
+    pub fn flag_c(&self) -> bool {
+        self.f & FLAG_C != 0
+    }
+
+    pub fn set_flag(&mut self, flag: u8, on: bool) {
+        if on {
+            self.f |= flag;
+        } else {
+            self.f &= !flag;
+        }
+        self.f &= 0xF0;
+    }
+
+    /// Set all four flags at once, in Z N H C order.
+    pub fn set_flags(&mut self, z: bool, n: bool, h: bool, c: bool) {
+        self.f = 0;
+        self.set_flag(FLAG_Z, z);
+        self.set_flag(FLAG_N, n);
+        self.set_flag(FLAG_H, h);
+        self.set_flag(FLAG_C, c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_combine_and_split() {
+        let mut r = Registers::default();
+        r.set_bc(0x1234);
+        assert_eq!(r.b, 0x12);
+        assert_eq!(r.c, 0x34);
+        assert_eq!(r.bc(), 0x1234);
+    }
+
+    #[test]
+    fn set_af_masks_low_nibble() {
+        let mut r = Registers::default();
+        r.set_af(0xABCD);
+        assert_eq!(r.a, 0xAB);
+        assert_eq!(r.f, 0xC0);
+    }
+}
@@ -0,0 +1,117 @@
+//! Breakpoint and single-step debugging around a [`System`].
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+
+use crate::cpu::registers::Registers;
+use crate::system::System;
+
+/// Wraps a [`System`] with PC breakpoints and inspection helpers, for the
+/// CLI debug REPL or embedding in other tools.
+pub struct Debugger {
+    pub system: System,
+    breakpoints: BTreeSet<u16>,
+}
+
+impl Debugger {
+    #[must_use]
+    pub fn new(system: System) -> Self {
+        Self {
+            system,
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    /// Returns whether the breakpoint existed.
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    /// The breakpoint addresses, sorted.
+    #[must_use]
+    pub fn breakpoints(&self) -> Vec<u16> {
+        self.breakpoints.iter().copied().collect()
+    }
+
+    /// Execute exactly one instruction (or interrupt dispatch). Returns the
+    /// cycles consumed.
+    pub fn step(&mut self) -> Result<usize> {
+        self.system.step()
+    }
+
+    /// Run until the PC lands on a breakpoint, checking before each
+    /// instruction. Always executes at least one instruction, so continuing
+    /// from a breakpoint makes progress. Returns the PC stopped at.
+    pub fn continue_until_break(&mut self) -> Result<u16> {
+        loop {
+            self.system.step()?;
+            let pc = self.system.cpu.regs.pc;
+            if self.breakpoints.contains(&pc) {
+                return Ok(pc);
+            }
+        }
+    }
+
+    /// The CPU register file.
+    #[must_use]
+    pub fn registers(&self) -> &Registers {
+        &self.system.cpu.regs
+    }
+
+    /// Read a byte off the bus, with full MMU routing.
+    #[must_use]
+    pub fn read_memory(&self, addr: u16) -> u8 {
+        self.system.mmu.read(addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::Cartridge;
+
+    fn debugger() -> Debugger {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x106].copy_from_slice(&[
+            0x00, // NOP
+            0x3E, 0x42, // LD A,0x42
+            0x04, // INC B
+            0x18, 0xFE, // JR -2
+        ]);
+        Debugger::new(System::new(Cartridge::new(rom).unwrap()))
+    }
+
+    #[test]
+    fn continue_stops_exactly_on_the_breakpoint() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(0x0103);
+        assert_eq!(debugger.continue_until_break().unwrap(), 0x0103);
+        assert_eq!(debugger.registers().a, 0x42, "LD A ran before the break");
+        assert_eq!(debugger.registers().b, 0x00, "INC B has not run yet");
+    }
+
+    #[test]
+    fn single_step_advances_one_instruction() {
+        let mut debugger = debugger();
+        debugger.step().unwrap();
+        assert_eq!(debugger.registers().pc, 0x0101, "one NOP");
+        debugger.step().unwrap();
+        assert_eq!(debugger.registers().pc, 0x0103, "one two-byte load");
+    }
+
+    #[test]
+    fn breakpoints_can_be_listed_and_removed() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(0x0200);
+        debugger.add_breakpoint(0x0100);
+        assert_eq!(debugger.breakpoints(), vec![0x0100, 0x0200]);
+        assert!(debugger.remove_breakpoint(0x0200));
+        assert!(!debugger.remove_breakpoint(0x0200));
+        assert_eq!(debugger.breakpoints(), vec![0x0100]);
+    }
+}
@@ -0,0 +1,98 @@
+//! The one-stop embedding API: a [`System`] behind a small, stable surface.
+
+use anyhow::Result;
+
+use crate::cartridge::{Cartridge, CartridgeError};
+use crate::joypad::Button;
+use crate::system::System;
+
+/// A complete Game Boy behind a minimal API, for frontends that just want
+/// frames in and buttons out without touching the CPU/MMU split.
+///
+/// ```
+/// use core_lib::Emulator;
+/// use core_lib::joypad::Button;
+///
+/// let mut emulator = Emulator::new(vec![0u8; 0x8000]).unwrap();
+/// emulator.press(Button::Start);
+/// let frame = emulator.run_frame().unwrap();
+/// assert_eq!(frame.len(), 160 * 144);
+/// emulator.release(Button::Start);
+/// ```
+///
+/// The wrapped [`System`] stays reachable through [`Emulator::system`] for
+/// anything this surface does not cover.
+pub struct Emulator {
+    system: System,
+}
+
+impl Emulator {
+    /// Build an emulator from a raw ROM image, in the post-boot state.
+    pub fn new(rom: Vec<u8>) -> Result<Self> {
+        Ok(Self {
+            system: System::new(Cartridge::new(rom)?),
+        })
+    }
+
+    /// Emulate until the next frame completes and return its 160×144 shade
+    /// indices (0–3), row-major.
+    pub fn run_frame(&mut self) -> Result<&[u8]> {
+        self.system.run_frame()
+    }
+
+    /// The last completed frame, without advancing emulation.
+    #[must_use]
+    pub fn frame_buffer(&self) -> &[u8] {
+        self.system.mmu.frame_buffer()
+    }
+
+    pub fn press(&mut self, button: Button) {
+        self.system.mmu.set_button(button, true);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.system.mmu.set_button(button, false);
+    }
+
+    /// The battery-backed cartridge RAM to persist, or `None` when the
+    /// cartridge has no battery.
+    #[must_use]
+    pub fn save_ram(&self) -> Option<Vec<u8>> {
+        let cart = self.system.mmu.cartridge();
+        cart.has_battery().then(|| cart.ram().to_vec())
+    }
+
+    /// Install previously saved cartridge RAM (a `.sav` image). The size
+    /// must match what the ROM header declares.
+    pub fn load_ram(&mut self, ram: Vec<u8>) -> Result<(), CartridgeError> {
+        self.system.mmu.cartridge_mut().install_ram(ram)
+    }
+
+    /// Serialize the full machine state (CPU and everything behind the MMU,
+    /// minus the ROM image) into one blob for [`Emulator::restore`].
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> Result<Vec<u8>> {
+        let mmu_state = self.system.mmu.save_state()?;
+        Ok(bincode::serialize(&(&self.system.cpu, mmu_state))?)
+    }
+
+    /// Restore an [`Emulator::snapshot`] blob. The emulator must have been
+    /// built from the same ROM.
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<()> {
+        let (cpu, mmu_state): (crate::cpu::Cpu, Vec<u8>) = bincode::deserialize(bytes)?;
+        self.system.mmu.load_state(&mmu_state)?;
+        self.system.cpu = cpu;
+        Ok(())
+    }
+
+    /// The underlying [`System`], for APIs beyond this facade.
+    #[must_use]
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    pub fn system_mut(&mut self) -> &mut System {
+        &mut self.system
+    }
+}
@@ -0,0 +1,98 @@
+//! The five Game Boy interrupt sources and their vectors.
+
+/// Interrupt sources, in priority order (VBlank highest).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    VBlank,
+    LcdStat,
+    Timer,
+    Serial,
+    Joypad,
+}
+
+impl Interrupt {
+    /// Bit mask of this interrupt in the IF/IE registers.
+    #[must_use]
+    pub const fn mask(self) -> u8 {
+        match self {
+            Self::VBlank => 0x01,
+            Self::LcdStat => 0x02,
+            Self::Timer => 0x04,
+            Self::Serial => 0x08,
+            Self::Joypad => 0x10,
+        }
+    }
+
+    /// Address the CPU jumps to when servicing this interrupt.
+    #[must_use]
+    pub const fn vector(self) -> u16 {
+        match self {
+            Self::VBlank => 0x0040,
+            Self::LcdStat => 0x0048,
+            Self::Timer => 0x0050,
+            Self::Serial => 0x0058,
+            Self::Joypad => 0x0060,
+        }
+    }
+
+    /// Map an IF/IE bit index (0..=4) back to the interrupt source.
+    #[must_use]
+    pub const fn from_bit(bit: u8) -> Option<Self> {
+        match bit {
+            0 => Some(Self::VBlank),
+            1 => Some(Self::LcdStat),
+            2 => Some(Self::Timer),
+            3 => Some(Self::Serial),
+            4 => Some(Self::Joypad),
+            _ => None,
+        }
+    }
+
+    /// The highest-priority source in a pending (IF ∧ IE) bit mask: the
+    /// lowest set bit, VBlank first. `None` when nothing is pending.
+    #[must_use]
+    pub const fn highest_priority(pending: u8) -> Option<Self> {
+        Self::from_bit((pending & 0x1F).trailing_zeros() as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// In priority order, as `highest_priority` must return them.
+    const ORDERED: [Interrupt; 5] = [
+        Interrupt::VBlank,
+        Interrupt::LcdStat,
+        Interrupt::Timer,
+        Interrupt::Serial,
+        Interrupt::Joypad,
+    ];
+
+    #[test]
+    fn highest_priority_over_every_flag_subset() {
+        for subset in 0u8..0x20 {
+            // Drain the subset: each pick must be the highest-priority set
+            // bit, and clearing it must surface the next one.
+            let mut pending = subset;
+            for expected in ORDERED.iter().filter(|int| subset & int.mask() != 0) {
+                assert_eq!(
+                    Interrupt::highest_priority(pending),
+                    Some(*expected),
+                    "subset {subset:#07b}, pending {pending:#07b}"
+                );
+                pending &= !expected.mask();
+            }
+            assert_eq!(Interrupt::highest_priority(pending), None);
+        }
+    }
+
+    #[test]
+    fn bits_outside_the_five_sources_are_ignored() {
+        assert_eq!(Interrupt::highest_priority(0xE0), None);
+        assert_eq!(
+            Interrupt::highest_priority(0xF0 | Interrupt::Joypad.mask()),
+            Some(Interrupt::Joypad)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Joypad matrix behind the 0xFF00 register.
+
+/// All eight buttons of the joypad matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Up,
+    Down,
+    Left,
+    Right,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+/// Joypad state and the FF00 select bits.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Joypad {
+    up: bool,
+    down: bool,
+    left: bool,
+    right: bool,
+    a: bool,
+    b: bool,
+    select_button: bool,
+    start: bool,
+    /// Bits 4–5 of the last FF00 write (0 = group selected).
+    select: u8,
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self {
+            up: false,
+            down: false,
+            left: false,
+            right: false,
+            a: false,
+            b: false,
+            select_button: false,
+            start: false,
+            select: 0x30,
+        }
+    }
+}
+
+impl Joypad {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update a button. Returns `true` when a Joypad interrupt should
+    /// fire: a selected P1 line fell from high to low. Presses in an
+    /// unselected group, releases, and re-presses of a held button never
+    /// fire.
+    pub fn set_button(&mut self, button: Button, pressed: bool) -> bool {
+        let before = self.read() & 0x0F;
+        match button {
+            Button::Up => self.up = pressed,
+            Button::Down => self.down = pressed,
+            Button::Left => self.left = pressed,
+            Button::Right => self.right = pressed,
+            Button::A => self.a = pressed,
+            Button::B => self.b = pressed,
+            Button::Select => self.select_button = pressed,
+            Button::Start => self.start = pressed,
+        }
+        before & !(self.read() & 0x0F) != 0
+    }
+
+    pub fn write(&mut self, value: u8) {
+        self.select = value & 0x30;
+    }
+
+    /// Compose the FF00 read value: bits 6–7 read high, bits 4–5 reflect
+    /// the last write, and the low nibble ANDs together every selected
+    /// group (a pressed button in either group pulls its line low). With
+    /// neither group selected nothing pulls, so the nibble reads 0x0F.
+    #[must_use]
+    pub fn read(&self) -> u8 {
+        let mut low = 0x0F;
+        if self.select & 0x10 == 0 {
+            low &= self.direction_nibble();
+        }
+        if self.select & 0x20 == 0 {
+            low &= self.action_nibble();
+        }
+        0xC0 | self.select | low
+    }
+
+    fn direction_nibble(&self) -> u8 {
+        let mut n = 0x0F;
+        if self.right {
+            n &= !0x01;
+        }
+        if self.left {
+            n &= !0x02;
+        }
+        if self.up {
+            n &= !0x04;
+        }
+        if self.down {
+            n &= !0x08;
+        }
+        n
+    }
+
+    fn action_nibble(&self) -> u8 {
+        let mut n = 0x0F;
+        if self.a {
+            n &= !0x01;
+        }
+        if self.b {
+            n &= !0x02;
+        }
+        if self.select_button {
+            n &= !0x04;
+        }
+        if self.start {
+            n &= !0x08;
+        }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressing_a_selected_button_requests_interrupt() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10); // select actions
+        assert!(joypad.set_button(Button::A, true));
+        assert!(!joypad.set_button(Button::A, true), "held: no new edge");
+        assert!(!joypad.set_button(Button::A, false), "release: rising edge");
+    }
+
+    #[test]
+    fn presses_in_an_unselected_group_do_not_interrupt() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10); // select actions
+        assert!(!joypad.set_button(Button::Up, true));
+        joypad.write(0x30); // select neither
+        assert!(!joypad.set_button(Button::A, true));
+    }
+
+    #[test]
+    fn direction_group_reads_pressed_as_zero() {
+        let mut joypad = Joypad::new();
+        joypad.set_button(Button::Up, true);
+        joypad.write(0x20); // select directions
+        assert_eq!(joypad.read() & 0x04, 0);
+    }
+
+    #[test]
+    fn up_and_a_read_exactly_per_select_configuration() {
+        let mut joypad = Joypad::new();
+        joypad.set_button(Button::Up, true);
+        joypad.set_button(Button::A, true);
+
+        joypad.write(0x30); // neither group: nothing pulls the lines low
+        assert_eq!(joypad.read(), 0xFF);
+        joypad.write(0x20); // directions only: Up pulls bit 2
+        assert_eq!(joypad.read(), 0xEB);
+        joypad.write(0x10); // actions only: A pulls bit 0
+        assert_eq!(joypad.read(), 0xDE);
+        joypad.write(0x00); // both groups AND together
+        assert_eq!(joypad.read(), 0xCA);
+    }
+
+    #[test]
+    fn start_and_select_sit_on_the_action_high_bits() {
+        let mut joypad = Joypad::new();
+        joypad.set_button(Button::Select, true);
+        joypad.set_button(Button::Start, true);
+        joypad.write(0x10); // actions only
+        assert_eq!(joypad.read(), 0xD3);
+    }
+}
@@ -0,0 +1,24 @@
+//! gboxide emulator core: CPU, MMU, PPU, APU, timer, joypad and cartridge.
+
+pub mod apu;
+pub mod cartridge;
+pub mod cpu;
+pub mod debugger;
+pub mod emulator;
+pub mod input;
+pub mod interrupts;
+pub mod joypad;
+pub mod mmu;
+pub mod ppu;
+pub mod serial;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_byte_array;
+pub mod system;
+pub mod timer;
+
+pub use cartridge::{Cartridge, CartridgeError};
+pub use cpu::{Cpu, CpuError};
+pub use debugger::Debugger;
+pub use emulator::Emulator;
+pub use mmu::Mmu;
+pub use system::System;
@@ -0,0 +1,1258 @@
+//! Pixel processing unit: LCD mode state machine and background rendering.
+
+use common::Color;
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
+const DOTS_PER_LINE: usize = 456;
+const LINES_PER_FRAME: u8 = 154;
+const MODE2_END: usize = 80;
+const MODE3_END: usize = 252;
+/// Dots into line 153 after which LY already reads 0 (the short-line quirk).
+const LINE_153_QUIRK_DOTS: usize = 4;
+
+/// Interrupts one [`Ppu::step`] wants raised. VBlank and STAT are separate
+/// lines, and one step can assert both.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PpuInterrupts {
+    pub vblank: bool,
+    pub stat: bool,
+}
+
+/// What the MMU needs from a pixel processing unit. The built-in [`Ppu`]
+/// implements it; alternative renderers can be injected with
+/// [`crate::mmu::Mmu::with_ppu`].
+pub trait PpuInterface {
+    /// Bus read of an LCD register (0xFF40–0xFF4B).
+    fn read_reg(&self, addr: u16) -> u8;
+    /// Bus write of an LCD register.
+    fn write_reg(&mut self, addr: u16, value: u8);
+    /// Bus read of VRAM (`addr` in 0x8000–0x9FFF).
+    fn read_vram(&self, addr: u16) -> u8;
+    /// Bus write of VRAM.
+    fn write_vram(&mut self, addr: u16, value: u8);
+    /// Bus read of OAM (`addr` in 0xFE00–0xFE9F).
+    fn read_oam(&self, addr: u16) -> u8;
+    /// Bus write of OAM.
+    fn write_oam(&mut self, addr: u16, value: u8);
+    /// OAM write from the DMA unit, which is never mode-blocked.
+    fn dma_write_oam(&mut self, index: u8, value: u8);
+    /// Debugger read of VRAM, ignoring mode blocking. The default falls
+    /// back to the bus accessor for renderers without separate storage.
+    fn peek_vram(&self, addr: u16) -> u8 {
+        self.read_vram(addr)
+    }
+    /// Debugger write of VRAM, ignoring mode blocking.
+    fn poke_vram(&mut self, addr: u16, value: u8) {
+        self.write_vram(addr, value);
+    }
+    /// Debugger read of OAM, ignoring mode blocking.
+    fn peek_oam(&self, addr: u16) -> u8 {
+        self.read_oam(addr)
+    }
+    /// Debugger write of OAM, ignoring mode blocking.
+    fn poke_oam(&mut self, addr: u16, value: u8) {
+        self.write_oam(addr, value);
+    }
+    /// Advance by `cycles` T-cycles and report interrupts to raise.
+    fn step(&mut self, cycles: usize) -> PpuInterrupts;
+    /// Shade indices for the last completed frame, row-major 160x144.
+    fn get_frame_buffer(&self) -> &[u8];
+    /// Number of frames completed so far.
+    fn frames_rendered(&self) -> u64;
+}
+
+/// Pixel layouts [`Ppu::frame`] can emit, so frontends get their native
+/// format without post-processing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8888,
+    Bgra8888,
+    Rgb888,
+    Rgb565,
+}
+
+impl PixelFormat {
+    /// Bytes one pixel occupies in this layout.
+    #[must_use]
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8888 | Self::Bgra8888 => 4,
+            Self::Rgb888 => 3,
+            Self::Rgb565 => 2,
+        }
+    }
+}
+
+/// A CGB compatibility colour scheme for DMG software: four colours each
+/// for the background and the two object palettes, indexed by palette-mapped
+/// shade. Frontends use it instead of [`Color::from_dmg_shade`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmgPalette {
+    pub bg: [Color; 4],
+    pub obj0: [Color; 4],
+    pub obj1: [Color; 4],
+}
+
+impl DmgPalette {
+    /// The plain DMG greys; the fallback for unrecognised checksums.
+    pub const GREY: Self = Self::uniform([
+        Color::new(0xFF, 0xFF, 0xFF),
+        Color::new(0xAA, 0xAA, 0xAA),
+        Color::new(0x55, 0x55, 0x55),
+        Color::new(0x00, 0x00, 0x00),
+    ]);
+    /// The CGB "Up" manual scheme.
+    pub const BROWN: Self = Self::uniform([
+        Color::new(0xFF, 0xFF, 0xFF),
+        Color::new(0xFF, 0xAD, 0x63),
+        Color::new(0x84, 0x31, 0x00),
+        Color::new(0x00, 0x00, 0x00),
+    ]);
+    /// The CGB "Up + A" manual scheme.
+    pub const RED: Self = Self::uniform([
+        Color::new(0xFF, 0xFF, 0xFF),
+        Color::new(0xFF, 0x85, 0x84),
+        Color::new(0x94, 0x3A, 0x3A),
+        Color::new(0x00, 0x00, 0x00),
+    ]);
+    /// The CGB "Down + A" manual scheme.
+    pub const ORANGE: Self = Self::uniform([
+        Color::new(0xFF, 0xFF, 0xFF),
+        Color::new(0xFF, 0xFF, 0x00),
+        Color::new(0xFF, 0x00, 0x00),
+        Color::new(0x00, 0x00, 0x00),
+    ]);
+    /// The CGB "Right" manual scheme.
+    pub const GREEN: Self = Self::uniform([
+        Color::new(0xFF, 0xFF, 0xFF),
+        Color::new(0x51, 0xFF, 0x00),
+        Color::new(0xFF, 0x42, 0x00),
+        Color::new(0x00, 0x00, 0x00),
+    ]);
+
+    /// The original DMG LCD's pea-green tones, for the classic look.
+    pub const LCD_GREEN: Self = Self::uniform([
+        Color::new(0x9B, 0xBC, 0x0F),
+        Color::new(0x8B, 0xAC, 0x0F),
+        Color::new(0x30, 0x62, 0x30),
+        Color::new(0x0F, 0x38, 0x0F),
+    ]);
+
+    /// A scheme using the same four colours for BG and both OBJ palettes,
+    /// which is all the manual schemes do.
+    #[must_use]
+    pub const fn uniform(colors: [Color; 4]) -> Self {
+        Self {
+            bg: colors,
+            obj0: colors,
+            obj1: colors,
+        }
+    }
+}
+
+impl Default for DmgPalette {
+    fn default() -> Self {
+        Self::GREY
+    }
+}
+
+/// Subset of the CGB boot ROM's title-checksum → colour-scheme table. The
+/// checksum is the wrapping byte sum of the header title (0x0134–0x0143).
+const DMG_PALETTE_TABLE: &[(u8, DmgPalette)] = &[
+    (0x14, DmgPalette::RED),    // POKEMON RED
+    (0x46, DmgPalette::ORANGE), // SUPER MARIOLAND
+    (0x70, DmgPalette::GREEN),  // ZELDA
+    (0xDB, DmgPalette::BROWN),  // TETRIS
+];
+
+/// The two tile-data addressing modes (LCDC bit 4): unsigned indices from
+/// 0x8000, or signed indices around 0x9000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileDataArea {
+    /// 0x8000–0x8FFF, `tile_index` taken as unsigned.
+    Unsigned8000,
+    /// 0x8800–0x97FF, `tile_index` taken as signed around 0x9000.
+    Signed8800,
+}
+
+/// The two 32×32 background/window tile maps (LCDC bits 3 and 6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileMap {
+    /// 0x9800–0x9BFF.
+    Low9800,
+    /// 0x9C00–0x9FFF.
+    High9C00,
+}
+
+/// One OAM entry selected by OAM search for a scanline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpriteRef {
+    pub oam_index: u8,
+    /// Raw OAM Y (screen Y + 16).
+    pub y: u8,
+    /// Raw OAM X (screen X + 8).
+    pub x: u8,
+    pub tile: u8,
+    pub flags: u8,
+}
+
+/// LCD controller state. VRAM and OAM live here so rendering never has to
+/// reach back into the MMU.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ppu {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_byte_array"))]
+    pub vram: [u8; 0x2000],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_byte_array"))]
+    pub oam: [u8; 0xA0],
+    lcdc: u8,
+    stat: u8,
+    scy: u8,
+    scx: u8,
+    ly: u8,
+    lyc: u8,
+    bgp: u8,
+    obp0: u8,
+    obp1: u8,
+    wy: u8,
+    wx: u8,
+    dots: usize,
+    /// One BGP-mapped shade (0–3) per pixel.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_byte_array"))]
+    frame: [u8; SCREEN_WIDTH * SCREEN_HEIGHT],
+    /// Completed frames since power-on; bumps on VBlank entry.
+    frames_rendered: u64,
+    /// Internal window line counter: advances only on lines the window was
+    /// actually drawn, resets each frame.
+    window_line: u8,
+    /// Hardware-accurate access gating: VRAM is unreadable in mode 3 and
+    /// OAM in modes 2–3. Off = lenient, for games that assume an emulator
+    /// which never blocks.
+    strict_vram_access: bool,
+    /// Mid-scanline palette writes on the current line: the pre-write BGP,
+    /// OBP0 and OBP1 values keyed by the first pixel the write reaches.
+    /// Raster effects rewrite BGP during mode 3; the whole-line renderer
+    /// replays these so each pixel sees the palette in effect at its dot.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    line_palette_events: Vec<(u8, u8, u8, u8)>,
+    /// Colour scheme frontends map shades through; cosmetic, so not part of
+    /// save states.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dmg_palette: DmgPalette,
+}
+
+impl Default for Ppu {
+    fn default() -> Self {
+        Self {
+            vram: [0; 0x2000],
+            oam: [0; 0xA0],
+            lcdc: 0x91,
+            stat: 0,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            bgp: 0xFC,
+            obp0: 0xFF,
+            obp1: 0xFF,
+            wy: 0,
+            wx: 0,
+            dots: 0,
+            frame: [0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            frames_rendered: 0,
+            window_line: 0,
+            strict_vram_access: true,
+            line_palette_events: Vec::new(),
+            dmg_palette: DmgPalette::GREY,
+        }
+    }
+}
+
+impl Ppu {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shade indices for the last completed frame, row-major 160x144.
+    #[must_use]
+    pub fn get_frame_buffer(&self) -> &[u8] {
+        &self.frame
+    }
+
+    /// The last completed frame converted to `format`, with shades mapped
+    /// through the active DMG colour scheme. Alpha channels are opaque and
+    /// RGB565 pixels are little-endian.
+    #[must_use]
+    pub fn frame(&self, format: PixelFormat) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.frame.len() * format.bytes_per_pixel());
+        for &shade in &self.frame {
+            let color = self.dmg_palette.bg[(shade & 0x03) as usize];
+            match format {
+                PixelFormat::Rgba8888 => {
+                    out.extend_from_slice(&[color.r, color.g, color.b, 0xFF]);
+                }
+                PixelFormat::Bgra8888 => {
+                    out.extend_from_slice(&[color.b, color.g, color.r, 0xFF]);
+                }
+                PixelFormat::Rgb888 => out.extend_from_slice(&[color.r, color.g, color.b]),
+                PixelFormat::Rgb565 => out.extend_from_slice(&color.to_rgb565().to_le_bytes()),
+            }
+        }
+        out
+    }
+
+    /// The last completed frame as RGBA8888 bytes, for screenshot and PNG
+    /// export without knowing the [`Color`] layout. Shorthand for
+    /// [`Ppu::frame`] with [`PixelFormat::Rgba8888`].
+    #[must_use]
+    pub fn framebuffer_rgba(&self) -> Vec<u8> {
+        self.frame(PixelFormat::Rgba8888)
+    }
+
+    /// LY as software sees it. On hardware line 153 is short: LY reads 153
+    /// for only its first few dots, then 0 for the rest of VBlank, which is
+    /// when an LYC=0 coincidence fires.
+    #[must_use]
+    pub fn ly(&self) -> u8 {
+        if self.ly == LINES_PER_FRAME - 1 && self.dots >= LINE_153_QUIRK_DOTS {
+            0
+        } else {
+            self.ly
+        }
+    }
+
+    /// Number of frames completed so far.
+    #[must_use]
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames_rendered
+    }
+
+    /// Toggle mode-based VRAM/OAM access gating. `false` is lenient: every
+    /// access goes through regardless of the current mode.
+    pub fn set_strict_vram_access(&mut self, on: bool) {
+        self.strict_vram_access = on;
+    }
+
+    /// Pick a colour scheme for a DMG game from its header title checksum,
+    /// as the CGB boot ROM does. Unrecognised checksums keep the greys.
+    pub fn apply_cgb_dmg_palette(&mut self, title_checksum: u8) {
+        self.dmg_palette = DMG_PALETTE_TABLE
+            .iter()
+            .find(|(checksum, _)| *checksum == title_checksum)
+            .map_or(DmgPalette::GREY, |(_, palette)| *palette);
+    }
+
+    /// Manually override the colour scheme, replacing any checksum pick.
+    pub fn set_dmg_palette(&mut self, palette: DmgPalette) {
+        self.dmg_palette = palette;
+    }
+
+    /// The colour scheme in effect for mapping framebuffer shades.
+    #[must_use]
+    pub fn dmg_palette(&self) -> DmgPalette {
+        self.dmg_palette
+    }
+
+    /// Redraw all 144 lines from current VRAM/OAM/register state without
+    /// advancing any timing. For debug tools that poke memory and want to
+    /// see the result immediately; emulation resumes exactly where it was.
+    pub fn rerender_current_frame(&mut self) {
+        let (saved_ly, saved_window_line) = (self.ly, self.window_line);
+        // Mid-line palette events belong to the line in progress, not to
+        // this whole-frame redraw.
+        let saved_events = std::mem::take(&mut self.line_palette_events);
+        self.window_line = 0;
+        for ly in 0..SCREEN_HEIGHT as u8 {
+            self.ly = ly;
+            self.render_scanline();
+        }
+        self.ly = saved_ly;
+        self.window_line = saved_window_line;
+        self.line_palette_events = saved_events;
+    }
+
+    fn mode(&self) -> u8 {
+        self.stat & 0x03
+    }
+
+    /// Whether the CPU can reach VRAM right now (blocked in mode 3).
+    fn vram_accessible(&self) -> bool {
+        !self.strict_vram_access || self.lcdc & 0x80 == 0 || self.mode() != 3
+    }
+
+    /// Whether the CPU can reach OAM right now (blocked in modes 2–3).
+    fn oam_accessible(&self) -> bool {
+        !self.strict_vram_access || self.lcdc & 0x80 == 0 || self.mode() < 2
+    }
+
+    /// Bus read of VRAM (`addr` in 0x8000–0x9FFF); 0xFF while blocked.
+    #[must_use]
+    pub fn read_vram(&self, addr: u16) -> u8 {
+        if self.vram_accessible() {
+            self.vram[(addr - 0x8000) as usize]
+        } else {
+            0xFF
+        }
+    }
+
+    /// Bus write to VRAM; dropped while blocked.
+    pub fn write_vram(&mut self, addr: u16, value: u8) {
+        if self.vram_accessible() {
+            self.vram[(addr - 0x8000) as usize] = value;
+        }
+    }
+
+    /// Bus read of OAM (`addr` in 0xFE00–0xFE9F); 0xFF while blocked.
+    #[must_use]
+    pub fn read_oam(&self, addr: u16) -> u8 {
+        if self.oam_accessible() {
+            self.oam[(addr - 0xFE00) as usize]
+        } else {
+            0xFF
+        }
+    }
+
+    /// Bus write to OAM; dropped while blocked.
+    pub fn write_oam(&mut self, addr: u16, value: u8) {
+        if self.oam_accessible() {
+            self.oam[(addr - 0xFE00) as usize] = value;
+        }
+    }
+
+    /// The (up to 10) sprites OAM search would select for line `ly`, in
+    /// draw-priority order: lowest X first, OAM index breaking ties. A pure
+    /// query over current OAM and LCDC; useful for sprite debugging.
+    #[must_use]
+    pub fn visible_sprites(&self, ly: u8) -> Vec<SpriteRef> {
+        let height = if self.lcdc & 0x04 != 0 { 16 } else { 8 };
+        let line = i32::from(ly) + 16;
+        let mut sprites: Vec<SpriteRef> = self
+            .oam
+            .chunks_exact(4)
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let y = i32::from(entry[0]);
+                (line >= y && line < y + height).then(|| SpriteRef {
+                    oam_index: index as u8,
+                    y: entry[0],
+                    x: entry[1],
+                    tile: entry[2],
+                    flags: entry[3],
+                })
+            })
+            .take(10)
+            .collect();
+        sprites.sort_by_key(|sprite| (sprite.x, sprite.oam_index));
+        sprites
+    }
+
+    /// Advance by `cycles` dots, reporting which interrupt lines fired.
+    pub fn step(&mut self, cycles: usize) -> PpuInterrupts {
+        let mut irq = PpuInterrupts::default();
+        if self.lcdc & 0x80 == 0 {
+            self.ly = 0;
+            self.dots = 0;
+            self.set_mode(0);
+            return irq;
+        }
+
+        self.dots += cycles;
+        while self.dots >= DOTS_PER_LINE {
+            self.dots -= DOTS_PER_LINE;
+            if self.ly < SCREEN_HEIGHT as u8 {
+                self.render_scanline();
+            }
+            self.line_palette_events.clear();
+            self.ly += 1;
+            if self.ly == SCREEN_HEIGHT as u8 {
+                irq.vblank = true;
+                self.frames_rendered += 1;
+            }
+            if self.ly >= LINES_PER_FRAME {
+                self.ly = 0;
+                self.window_line = 0;
+            }
+            self.update_coincidence(&mut irq);
+        }
+        // Re-evaluate mid-line so the LY=153 early-zero transition (and any
+        // LYC rewrite) is seen at the dot it happens, not a line later.
+        self.update_coincidence(&mut irq);
+
+        let mode = if self.ly >= SCREEN_HEIGHT as u8 {
+            1
+        } else if self.dots < MODE2_END {
+            2
+        } else if self.dots < MODE3_END {
+            3
+        } else {
+            0
+        };
+        self.enter_mode(mode, &mut irq);
+        irq
+    }
+
+    /// Refresh the LYC==LY bit (STAT bit 2) and fire the STAT line on a
+    /// rising coincidence when its enable bit (6) is set.
+    fn update_coincidence(&mut self, irq: &mut PpuInterrupts) {
+        let equal = self.ly() == self.lyc;
+        let was_equal = self.stat & 0x04 != 0;
+        self.stat = (self.stat & !0x04) | (u8::from(equal) << 2);
+        if equal && !was_equal && self.stat & 0x40 != 0 {
+            irq.stat = true;
+        }
+    }
+
+    /// Switch STAT to `mode`, firing the STAT line when the new mode's
+    /// interrupt-enable bit (3: HBlank, 4: VBlank, 5: OAM) is set.
+    fn enter_mode(&mut self, mode: u8, irq: &mut PpuInterrupts) {
+        if self.stat & 0x03 == mode {
+            return;
+        }
+        self.set_mode(mode);
+        let enable = match mode {
+            0 => 0x08,
+            1 => 0x10,
+            2 => 0x20,
+            _ => 0,
+        };
+        if self.stat & enable != 0 {
+            irq.stat = true;
+        }
+    }
+
+    pub fn read_reg(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF40 => self.lcdc,
+            0xFF41 => self.stat | 0x80,
+            0xFF42 => self.scy,
+            0xFF43 => self.scx,
+            0xFF44 => self.ly(),
+            0xFF45 => self.lyc,
+            0xFF47 => self.bgp,
+            0xFF48 => self.obp0,
+            0xFF49 => self.obp1,
+            0xFF4A => self.wy,
+            0xFF4B => self.wx,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_reg(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF40 => self.lcdc = value,
+            0xFF41 => self.stat = (self.stat & 0x07) | (value & 0x78),
+            0xFF42 => self.scy = value,
+            0xFF43 => self.scx = value,
+            0xFF44 => {} // LY is read-only
+            0xFF45 => self.lyc = value,
+            0xFF47 => {
+                self.latch_palette_event();
+                self.bgp = value;
+            }
+            0xFF48 => {
+                self.latch_palette_event();
+                self.obp0 = value;
+            }
+            0xFF49 => {
+                self.latch_palette_event();
+                self.obp1 = value;
+            }
+            0xFF4A => self.wy = value,
+            0xFF4B => self.wx = value,
+            _ => {}
+        }
+    }
+
+    fn set_mode(&mut self, mode: u8) {
+        self.stat = (self.stat & !0x03) | (mode & 0x03);
+    }
+
+    /// Raw color index (0–3) of a BG/window tile pixel. `map_base` is the
+    /// tilemap offset into VRAM; the data area follows LCDC bit 4.
+    /// Decode one tile into its 2bpp shade indices, row-major, for tile
+    /// viewers. Reads the PPU's VRAM copy directly, ignoring mode locks.
+    #[must_use]
+    pub fn tile_pixels(&self, tile_index: u8, data_area: TileDataArea) -> [[u8; 8]; 8] {
+        let tile_addr = match data_area {
+            TileDataArea::Unsigned8000 => tile_index as usize * 16,
+            TileDataArea::Signed8800 => (0x1000_i32 + i32::from(tile_index as i8) * 16) as usize,
+        };
+        let mut pixels = [[0u8; 8]; 8];
+        for (y, row) in pixels.iter_mut().enumerate() {
+            let lo = self.vram[tile_addr + y * 2];
+            let hi = self.vram[tile_addr + y * 2 + 1];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let bit = 7 - x;
+                *pixel = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+            }
+        }
+        pixels
+    }
+
+    /// The tile indices of a background map, row-major, for tilemap viewers.
+    #[must_use]
+    pub fn tilemap(&self, map: TileMap) -> [[u8; 32]; 32] {
+        let base = match map {
+            TileMap::Low9800 => 0x1800,
+            TileMap::High9C00 => 0x1C00,
+        };
+        let mut tiles = [[0u8; 32]; 32];
+        for (y, row) in tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                *tile = self.vram[base + y * 32 + x];
+            }
+        }
+        tiles
+    }
+
+    /// Record the current palette set before a mid-mode-3 write, keyed by
+    /// the pixel the line has reached, so [`Ppu::render_scanline`] can give
+    /// earlier pixels the pre-write values.
+    fn latch_palette_event(&mut self) {
+        if self.lcdc & 0x80 != 0 && self.mode() == 3 && self.ly < SCREEN_HEIGHT as u8 {
+            let x = self.dots.saturating_sub(MODE2_END).min(SCREEN_WIDTH) as u8;
+            self.line_palette_events.push((x, self.bgp, self.obp0, self.obp1));
+        }
+    }
+
+    /// BGP as seen by pixel `x` of the line being rendered: the value before
+    /// the first mid-line write past `x`, or the live register.
+    fn bgp_at(&self, x: usize) -> u8 {
+        self.line_palette_events
+            .iter()
+            .find(|&&(ex, ..)| x < ex as usize)
+            .map_or(self.bgp, |&(_, bgp, ..)| bgp)
+    }
+
+    /// OBP0/OBP1 as seen by pixel `x`, like [`Ppu::bgp_at`].
+    fn obp_at(&self, x: usize, high: bool) -> u8 {
+        let live = if high { self.obp1 } else { self.obp0 };
+        self.line_palette_events
+            .iter()
+            .find(|&&(ex, ..)| x < ex as usize)
+            .map_or(live, |&(_, _, obp0, obp1)| if high { obp1 } else { obp0 })
+    }
+
+    fn tile_color_id(&self, map_base: usize, map_x: usize, map_y: usize, fine_x: u8, fine_y: u8) -> u8 {
+        let tile_index = self.vram[map_base + (map_y % 32) * 32 + (map_x % 32)];
+        let tile_addr = if self.lcdc & 0x10 != 0 {
+            tile_index as usize * 16
+        } else {
+            (0x1000_i32 + i32::from(tile_index as i8) * 16) as usize
+        };
+        let line = fine_y as usize * 2;
+        let lo = self.vram[tile_addr + line];
+        let hi = self.vram[tile_addr + line + 1];
+        let bit = 7 - fine_x;
+        ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1)
+    }
+
+    /// Draw the current LY: background, then window, then sprites with
+    /// OBJ-to-BG priority.
+    fn render_scanline(&mut self) {
+        let row = self.ly as usize * SCREEN_WIDTH;
+        // Pre-palette BG/window color ids; sprites need them for priority
+        // and color-0 transparency.
+        let mut color_ids = [0u8; SCREEN_WIDTH];
+
+        if self.lcdc & 0x01 != 0 {
+            let map_base: usize = if self.lcdc & 0x08 != 0 { 0x1C00 } else { 0x1800 };
+            let y = self.ly.wrapping_add(self.scy);
+            for x in 0..SCREEN_WIDTH as u8 {
+                let px = x.wrapping_add(self.scx);
+                color_ids[x as usize] =
+                    self.tile_color_id(map_base, (px / 8) as usize, (y / 8) as usize, px % 8, y % 8);
+            }
+
+            // The window replaces the BG from WX-7 rightward once WY has
+            // been reached, reading its own line counter.
+            if self.lcdc & 0x20 != 0 && self.wy <= self.ly && self.wx <= 166 {
+                let map_base: usize = if self.lcdc & 0x40 != 0 { 0x1C00 } else { 0x1800 };
+                let wy = self.window_line;
+                let start = self.wx.saturating_sub(7) as usize;
+                for (x, id) in color_ids.iter_mut().enumerate().skip(start) {
+                    let wx = (x + 7 - self.wx as usize) as u8;
+                    *id = self.tile_color_id(map_base, (wx / 8) as usize, (wy / 8) as usize, wx % 8, wy % 8);
+                }
+                self.window_line += 1;
+            }
+        }
+
+        for (x, id) in color_ids.iter().enumerate() {
+            self.frame[row + x] = (self.bgp_at(x) >> (id * 2)) & 0x03;
+        }
+
+        if self.lcdc & 0x02 == 0 {
+            return;
+        }
+        let height = if self.lcdc & 0x04 != 0 { 16u8 } else { 8 };
+        // Reverse draw order: the highest-priority sprite lands last, on top.
+        for sprite in self.visible_sprites(self.ly).iter().rev() {
+            let mut line = self.ly + 16 - sprite.y;
+            if sprite.flags & 0x40 != 0 {
+                line = height - 1 - line; // Y flip
+            }
+            let mut tile = sprite.tile;
+            if height == 16 {
+                tile &= 0xFE; // tall sprites use an aligned tile pair
+            }
+            let tile_addr = tile as usize * 16 + line as usize * 2;
+            let lo = self.vram[tile_addr];
+            let hi = self.vram[tile_addr + 1];
+            let obp1 = sprite.flags & 0x10 != 0;
+            for px in 0..8u8 {
+                let sx = i32::from(sprite.x) - 8 + i32::from(px);
+                if !(0..SCREEN_WIDTH as i32).contains(&sx) {
+                    continue;
+                }
+                let bit = if sprite.flags & 0x20 != 0 { px } else { 7 - px }; // X flip
+                let color_id = ((hi >> bit) & 1) << 1 | ((lo >> bit) & 1);
+                if color_id == 0 {
+                    continue; // sprite color 0 is transparent
+                }
+                if sprite.flags & 0x80 != 0 && color_ids[sx as usize] != 0 {
+                    continue; // behind BG colors 1-3
+                }
+                let palette = self.obp_at(sx as usize, obp1);
+                self.frame[row + sx as usize] = (palette >> (color_id * 2)) & 0x03;
+            }
+        }
+    }
+}
+
+impl PpuInterface for Ppu {
+    fn read_reg(&self, addr: u16) -> u8 {
+        Self::read_reg(self, addr)
+    }
+
+    fn write_reg(&mut self, addr: u16, value: u8) {
+        Self::write_reg(self, addr, value);
+    }
+
+    fn read_vram(&self, addr: u16) -> u8 {
+        Self::read_vram(self, addr)
+    }
+
+    fn write_vram(&mut self, addr: u16, value: u8) {
+        Self::write_vram(self, addr, value);
+    }
+
+    fn read_oam(&self, addr: u16) -> u8 {
+        Self::read_oam(self, addr)
+    }
+
+    fn write_oam(&mut self, addr: u16, value: u8) {
+        Self::write_oam(self, addr, value);
+    }
+
+    fn dma_write_oam(&mut self, index: u8, value: u8) {
+        self.oam[index as usize] = value;
+    }
+
+    fn peek_vram(&self, addr: u16) -> u8 {
+        self.vram[(addr - 0x8000) as usize]
+    }
+
+    fn poke_vram(&mut self, addr: u16, value: u8) {
+        self.vram[(addr - 0x8000) as usize] = value;
+    }
+
+    fn peek_oam(&self, addr: u16) -> u8 {
+        self.oam[(addr - 0xFE00) as usize]
+    }
+
+    fn poke_oam(&mut self, addr: u16, value: u8) {
+        self.oam[(addr - 0xFE00) as usize] = value;
+    }
+
+    fn step(&mut self, cycles: usize) -> PpuInterrupts {
+        Self::step(self, cycles)
+    }
+
+    fn get_frame_buffer(&self) -> &[u8] {
+        Self::get_frame_buffer(self)
+    }
+
+    fn frames_rendered(&self) -> u64 {
+        Self::frames_rendered(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_checksum_picks_the_matching_cgb_scheme() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x134..0x134 + 15].copy_from_slice(b"SUPER MARIOLAND");
+        let cart = crate::cartridge::Cartridge::new(rom).unwrap();
+        assert_eq!(cart.header().title_checksum, 0x46);
+
+        let mut ppu = Ppu::new();
+        assert_eq!(ppu.dmg_palette(), DmgPalette::GREY);
+        ppu.apply_cgb_dmg_palette(cart.header().title_checksum);
+        assert_eq!(ppu.dmg_palette(), DmgPalette::ORANGE);
+
+        // Unknown checksums fall back to the greys…
+        ppu.apply_cgb_dmg_palette(0x00);
+        assert_eq!(ppu.dmg_palette(), DmgPalette::GREY);
+        // …and a manual override sticks.
+        ppu.set_dmg_palette(DmgPalette::GREEN);
+        assert_eq!(ppu.dmg_palette().bg[1], Color::new(0x51, 0xFF, 0x00));
+    }
+
+    #[test]
+    fn frame_formats_lay_out_a_white_pixel_per_spec() {
+        // A fresh PPU's frame is all shade 0, which the grey scheme maps
+        // to white.
+        let ppu = Ppu::new();
+        let pixels = SCREEN_WIDTH * SCREEN_HEIGHT;
+
+        let rgba = ppu.frame(PixelFormat::Rgba8888);
+        assert_eq!(rgba.len(), pixels * 4);
+        assert_eq!(&rgba[..4], &[0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let bgra = ppu.frame(PixelFormat::Bgra8888);
+        assert_eq!(bgra.len(), pixels * 4);
+        assert_eq!(&bgra[..4], &[0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let rgb = ppu.frame(PixelFormat::Rgb888);
+        assert_eq!(rgb.len(), pixels * 3);
+        assert_eq!(&rgb[..3], &[0xFF, 0xFF, 0xFF]);
+
+        let rgb565 = ppu.frame(PixelFormat::Rgb565);
+        assert_eq!(rgb565.len(), pixels * 2);
+        assert_eq!(&rgb565[..2], &0xFFFFu16.to_le_bytes());
+    }
+
+    #[test]
+    fn each_shade_converts_through_the_selected_palette() {
+        let mut ppu = Ppu::new();
+        ppu.frame[..4].copy_from_slice(&[0, 1, 2, 3]);
+
+        for palette in [DmgPalette::GREY, DmgPalette::LCD_GREEN] {
+            ppu.set_dmg_palette(palette);
+            let rgba = ppu.framebuffer_rgba();
+            for shade in 0..4 {
+                let expected = palette.bg[shade];
+                assert_eq!(
+                    &rgba[shade * 4..shade * 4 + 4],
+                    &[expected.r, expected.g, expected.b, 0xFF]
+                );
+            }
+        }
+
+        // The palettes must actually differ for the mid shades.
+        assert_ne!(DmgPalette::GREY.bg[1], DmgPalette::LCD_GREEN.bg[1]);
+    }
+
+    #[test]
+    fn an_all_white_frame_converts_to_all_0xff_rgba() {
+        // A fresh PPU's frame is all shade 0, which the default palette
+        // maps to white; with the opaque alpha that is 0xFF everywhere.
+        let ppu = Ppu::new();
+        let rgba = ppu.framebuffer_rgba();
+        assert_eq!(rgba.len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+        assert!(rgba.iter().all(|&byte| byte == 0xFF));
+    }
+
+    #[test]
+    fn frame_formats_distinguish_channel_order() {
+        let mut ppu = Ppu::new();
+        ppu.set_dmg_palette(DmgPalette::uniform([
+            Color::new(0x11, 0x22, 0x33),
+            Color::BLACK,
+            Color::BLACK,
+            Color::BLACK,
+        ]));
+        assert_eq!(&ppu.frame(PixelFormat::Rgba8888)[..4], &[0x11, 0x22, 0x33, 0xFF]);
+        assert_eq!(&ppu.frame(PixelFormat::Bgra8888)[..4], &[0x33, 0x22, 0x11, 0xFF]);
+    }
+
+    #[test]
+    fn ly_advances_and_wraps() {
+        let mut ppu = Ppu::new();
+        ppu.step(DOTS_PER_LINE);
+        assert_eq!(ppu.ly(), 1);
+        ppu.step(DOTS_PER_LINE * 153);
+        assert_eq!(ppu.ly(), 0);
+    }
+
+    #[test]
+    fn vblank_signalled_at_line_144() {
+        let mut ppu = Ppu::new();
+        for _ in 0..143 {
+            assert!(!ppu.step(DOTS_PER_LINE).vblank);
+        }
+        assert!(ppu.step(DOTS_PER_LINE).vblank);
+    }
+
+    #[test]
+    fn lyc_coincidence_fires_stat_when_enabled() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF45, 3); // LYC
+        ppu.write_reg(0xFF41, 0x40); // LYC interrupt enable
+
+        for line in 1..=2 {
+            let irq = ppu.step(DOTS_PER_LINE);
+            assert!(!irq.stat, "no coincidence at line {line}");
+        }
+        let irq = ppu.step(DOTS_PER_LINE);
+        assert!(irq.stat, "LY==LYC==3");
+        assert_eq!(ppu.read_reg(0xFF41) & 0x04, 0x04, "coincidence bit set");
+
+        let irq = ppu.step(DOTS_PER_LINE);
+        assert!(!irq.stat, "fires once, on the rising edge");
+        assert_eq!(ppu.read_reg(0xFF41) & 0x04, 0x00);
+    }
+
+    #[test]
+    fn lyc_zero_fires_in_line_153s_early_zero_window() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF45, 0); // LYC = 0
+        ppu.write_reg(0xFF41, 0x40); // LYC interrupt enable
+
+        let mut fired = false;
+        for _ in 0..153 {
+            fired |= ppu.step(DOTS_PER_LINE).stat;
+        }
+        assert!(!fired, "no LY=0 coincidence before line 153's quirk");
+        assert_eq!(ppu.read_reg(0xFF44), 153, "LY still reads 153 at dot 0");
+
+        let irq = ppu.step(LINE_153_QUIRK_DOTS);
+        assert!(irq.stat, "LYC=0 fires when LY drops early");
+        assert_eq!(ppu.read_reg(0xFF44), 0);
+
+        // The rest of line 153 and real line 0 keep LY=0 without refiring.
+        assert!(!ppu.step(DOTS_PER_LINE - LINE_153_QUIRK_DOTS).stat);
+        assert_eq!(ppu.read_reg(0xFF44), 0);
+    }
+
+    #[test]
+    fn mode_entry_fires_stat_per_enable_bit() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF41, 0x08); // HBlank (mode 0) enable
+        assert!(!ppu.step(MODE2_END + 1).stat, "mode 3 has no enable bit");
+        assert!(ppu.step(MODE3_END - MODE2_END).stat, "entered HBlank");
+
+        // VBlank entry raises both lines when bit 4 is set.
+        ppu.write_reg(0xFF41, 0x10);
+        let mut ppu2 = Ppu::new();
+        ppu2.write_reg(0xFF41, 0x10);
+        let mut irq = PpuInterrupts::default();
+        for _ in 0..144 {
+            irq = ppu2.step(DOTS_PER_LINE);
+        }
+        assert!(irq.vblank && irq.stat);
+    }
+
+    fn put_sprite(ppu: &mut Ppu, index: usize, y: u8, x: u8, tile: u8) {
+        ppu.oam[index * 4] = y;
+        ppu.oam[index * 4 + 1] = x;
+        ppu.oam[index * 4 + 2] = tile;
+    }
+
+    #[test]
+    fn visible_sprites_selects_by_line_and_sorts_by_x() {
+        let mut ppu = Ppu::new();
+        put_sprite(&mut ppu, 0, 16, 40, 0x01); // covers lines 0–7
+        put_sprite(&mut ppu, 1, 16, 20, 0x02); // same lines, further left
+        put_sprite(&mut ppu, 2, 30, 10, 0x03); // lines 14–21 only
+
+        let sprites = ppu.visible_sprites(4);
+        let picked: Vec<(u8, u8)> = sprites.iter().map(|s| (s.oam_index, s.tile)).collect();
+        assert_eq!(picked, vec![(1, 0x02), (0, 0x01)], "lower X draws first");
+        assert!(ppu.visible_sprites(16).iter().any(|s| s.oam_index == 2));
+    }
+
+    #[test]
+    fn visible_sprites_caps_at_ten_per_line() {
+        let mut ppu = Ppu::new();
+        for index in 0..12 {
+            put_sprite(&mut ppu, index, 16, index as u8 + 8, 0);
+        }
+        let sprites = ppu.visible_sprites(0);
+        assert_eq!(sprites.len(), 10);
+        // The cap keeps the first ten in OAM order, not the leftmost ten.
+        assert!(sprites.iter().all(|s| s.oam_index < 10));
+    }
+
+    #[test]
+    fn tall_sprite_mode_doubles_coverage() {
+        let mut ppu = Ppu::new();
+        put_sprite(&mut ppu, 0, 16, 8, 0);
+        assert!(ppu.visible_sprites(10).is_empty());
+        ppu.write_reg(0xFF40, ppu.read_reg(0xFF40) | 0x04);
+        assert_eq!(ppu.visible_sprites(10).len(), 1);
+    }
+
+    #[test]
+    fn window_overlays_the_background_from_wx() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4); // identity BGP
+        ppu.vram[0x10..0x20].fill(0xFF); // tile 1: solid color 3
+        for i in 0..32 {
+            ppu.vram[0x1C00 + i] = 1; // window map row 0
+        }
+        ppu.write_reg(0xFF40, 0x91 | 0x20 | 0x40); // window on, map 0x1C00
+        ppu.write_reg(0xFF4A, 4); // WY
+        ppu.write_reg(0xFF4B, 17); // WX: screen X 10
+
+        for _ in 0..5 {
+            ppu.step(DOTS_PER_LINE);
+        }
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[3 * SCREEN_WIDTH + 20], 0, "above WY: background");
+        assert_eq!(frame[4 * SCREEN_WIDTH + 9], 0, "left of WX: background");
+        assert_eq!(frame[4 * SCREEN_WIDTH + 10], 3, "window starts at WX-7");
+        assert_eq!(frame[4 * SCREEN_WIDTH + 159], 3, "window runs to the edge");
+    }
+
+    #[test]
+    fn overlapping_sprites_resolve_by_x_then_oam_index() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4);
+        ppu.write_reg(0xFF48, 0xE4); // OBP0: identity
+        ppu.write_reg(0xFF49, 0x40); // OBP1: color 3 -> shade 1
+        ppu.write_reg(0xFF40, 0x93); // LCD + BG + OBJ on
+        ppu.vram[0x20..0x30].fill(0xFF); // tile 2: solid color 3
+        put_sprite(&mut ppu, 0, 16, 8, 2); // screen X 0-7, OBP0
+        put_sprite(&mut ppu, 1, 16, 12, 2); // screen X 4-11, OBP1
+        ppu.oam[7] = 0x10; // sprite 1 uses OBP1
+
+        ppu.step(DOTS_PER_LINE);
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[0], 3, "sprite 0 alone");
+        assert_eq!(frame[4], 3, "overlap: the lower-X sprite wins");
+        assert_eq!(frame[8], 1, "past sprite 0: sprite 1 via OBP1");
+        assert_eq!(frame[12], 0, "past both: background");
+    }
+
+    /// A PPU with OBJ rendering on and tile 2 set to an asymmetric pattern:
+    /// two color-3 pixels in the top-left corner, everything else blank.
+    fn ppu_with_corner_tile() -> Ppu {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4);
+        ppu.write_reg(0xFF48, 0xE4); // OBP0: identity
+        ppu.write_reg(0xFF40, 0x93); // LCD + BG + OBJ on
+        ppu.vram[0x20] = 0xC0; // tile 2, row 0: pixels 0-1 color 3
+        ppu.vram[0x21] = 0xC0;
+        ppu
+    }
+
+    #[test]
+    fn sprite_renders_its_tile_unflipped_by_default() {
+        let mut ppu = ppu_with_corner_tile();
+        put_sprite(&mut ppu, 0, 16, 8, 2); // screen (0,0)
+
+        for _ in 0..8 {
+            ppu.step(DOTS_PER_LINE);
+        }
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(&frame[0..3], &[3, 3, 0], "marker in the top-left");
+        assert_eq!(frame[7], 0, "right edge blank");
+        assert_eq!(&frame[7 * SCREEN_WIDTH..7 * SCREEN_WIDTH + 2], &[0, 0], "bottom row blank");
+    }
+
+    #[test]
+    fn sprite_x_flip_mirrors_pixels_within_the_row() {
+        let mut ppu = ppu_with_corner_tile();
+        put_sprite(&mut ppu, 0, 16, 8, 2);
+        ppu.oam[3] = 0x20; // X flip
+
+        ppu.step(DOTS_PER_LINE);
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[0], 0, "left edge now blank");
+        assert_eq!(&frame[6..8], &[3, 3], "marker moved to the right edge");
+    }
+
+    #[test]
+    fn sprite_y_flip_mirrors_rows_within_the_tile() {
+        let mut ppu = ppu_with_corner_tile();
+        put_sprite(&mut ppu, 0, 16, 8, 2);
+        ppu.oam[3] = 0x40; // Y flip
+
+        for _ in 0..8 {
+            ppu.step(DOTS_PER_LINE);
+        }
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(&frame[0..2], &[0, 0], "top row now blank");
+        assert_eq!(
+            &frame[7 * SCREEN_WIDTH..7 * SCREEN_WIDTH + 3],
+            &[3, 3, 0],
+            "marker moved to the bottom row, still on the left"
+        );
+    }
+
+    #[test]
+    fn tall_sprites_stack_two_tiles_and_ignore_tile_bit_0() {
+        let mut ppu = ppu_with_corner_tile();
+        ppu.write_reg(0xFF40, ppu.read_reg(0xFF40) | 0x04); // 8x16 OBJ
+        ppu.vram[0x3E] = 0xFF; // tile 3 (bottom of the pair), row 7: solid
+        ppu.vram[0x3F] = 0xFF;
+        // OAM says tile 3, but 8x16 mode masks bit 0: the pair is 2/3.
+        put_sprite(&mut ppu, 0, 16, 8, 3);
+
+        for _ in 0..16 {
+            ppu.step(DOTS_PER_LINE);
+        }
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(&frame[0..3], &[3, 3, 0], "tile 2 row 0 on screen line 0");
+        assert_eq!(
+            &frame[15 * SCREEN_WIDTH..15 * SCREEN_WIDTH + 8],
+            &[3; 8],
+            "tile 3 row 7 on screen line 15"
+        );
+        assert_eq!(frame[8 * SCREEN_WIDTH], 0, "tile 3's blank rows between");
+    }
+
+    #[test]
+    fn tall_sprite_y_flip_swaps_the_tile_pair() {
+        let mut ppu = ppu_with_corner_tile();
+        ppu.write_reg(0xFF40, ppu.read_reg(0xFF40) | 0x04); // 8x16 OBJ
+        ppu.vram[0x3E] = 0xFF; // tile 3 (bottom of the pair), row 7: solid
+        ppu.vram[0x3F] = 0xFF;
+        put_sprite(&mut ppu, 0, 16, 8, 2);
+        ppu.oam[3] = 0x40; // Y flip
+
+        for _ in 0..16 {
+            ppu.step(DOTS_PER_LINE);
+        }
+        let frame = ppu.get_frame_buffer();
+        // The bottom tile's last row lands on screen line 0…
+        assert_eq!(&frame[0..8], &[3; 8], "tile 3 row 7 at the top");
+        // …and the top tile's first row on screen line 15.
+        assert_eq!(
+            &frame[15 * SCREEN_WIDTH..15 * SCREEN_WIDTH + 3],
+            &[3, 3, 0],
+            "tile 2 row 0 at the bottom"
+        );
+    }
+
+    #[test]
+    fn obj_behind_bg_shows_only_over_color_zero() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4);
+        ppu.write_reg(0xFF48, 0xE4);
+        ppu.write_reg(0xFF40, 0x93); // LCD + BG + OBJ on
+        for i in 0..8 {
+            ppu.vram[0x10 + i * 2] = 0xFF; // tile 1: solid color 1
+        }
+        ppu.vram[0x1800] = 1; // BG map: first column only
+        ppu.vram[0x20..0x30].fill(0xFF); // tile 2: solid color 3
+        put_sprite(&mut ppu, 0, 16, 12, 2); // screen X 4-11
+        ppu.oam[3] = 0x80; // behind BG colors 1-3
+
+        ppu.step(DOTS_PER_LINE);
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[4], 1, "BG color 1 hides the sprite");
+        assert_eq!(frame[8], 3, "BG color 0 lets it through");
+    }
+
+    #[test]
+    fn tile_pixels_decodes_2bpp_rows_in_both_addressing_modes() {
+        let mut ppu = Ppu::new();
+        // Tile 1, row 0: the classic 0x7E/0x81 smiley-border row.
+        ppu.vram[0x10] = 0x7E; // low bitplane
+        ppu.vram[0x11] = 0x81; // high bitplane
+
+        let tile = ppu.tile_pixels(1, TileDataArea::Unsigned8000);
+        assert_eq!(tile[0], [2, 1, 1, 1, 1, 1, 1, 2]);
+        assert_eq!(tile[1], [0; 8], "untouched rows decode to shade 0");
+
+        // Signed mode: index 0x80 reaches down to 0x8800.
+        ppu.vram[0x800] = 0xFF;
+        ppu.vram[0x801] = 0x00;
+        let tile = ppu.tile_pixels(0x80, TileDataArea::Signed8800);
+        assert_eq!(tile[0], [1; 8]);
+    }
+
+    #[test]
+    fn tilemap_returns_indices_for_the_selected_map() {
+        let mut ppu = Ppu::new();
+        ppu.vram[0x1800] = 0x42; // low map, top-left
+        ppu.vram[0x1C00 + 31 * 32 + 31] = 0x99; // high map, bottom-right
+
+        assert_eq!(ppu.tilemap(TileMap::Low9800)[0][0], 0x42);
+        assert_eq!(ppu.tilemap(TileMap::Low9800)[31][31], 0x00);
+        assert_eq!(ppu.tilemap(TileMap::High9C00)[31][31], 0x99);
+    }
+
+    #[test]
+    fn mid_scanline_bgp_write_splits_the_line() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4); // color 0 -> shade 0
+        ppu.step(MODE2_END + 80); // pixel 80 of line 0's transfer
+        assert_eq!(ppu.read_reg(0xFF41) & 0x03, 3);
+
+        ppu.write_reg(0xFF47, 0xE7); // color 0 -> shade 3
+        ppu.step(DOTS_PER_LINE - MODE2_END - 80); // finish the line
+        ppu.step(DOTS_PER_LINE); // and render line 1 normally
+
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[0], 0, "left half keeps the old palette");
+        assert_eq!(frame[79], 0);
+        assert_eq!(frame[80], 3, "the write lands at its pixel");
+        assert_eq!(frame[159], 3);
+        assert_eq!(frame[SCREEN_WIDTH], 3, "next line uses the live value");
+    }
+
+    #[test]
+    fn rerender_reflects_poked_vram_without_advancing_timing() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF47, 0xE4); // identity BGP
+        ppu.write_reg(0xFF40, 0x91); // LCD + BG on, map all tile 0
+        ppu.step(40); // partway into line 0's OAM search
+        assert_eq!(ppu.get_frame_buffer()[0], 0, "nothing drawn yet");
+
+        // Change tile 0's top row to color 3 behind the renderer's back.
+        ppu.poke_vram(0x8000, 0xFF);
+        ppu.poke_vram(0x8001, 0xFF);
+        let (ly, stat) = (ppu.read_reg(0xFF44), ppu.read_reg(0xFF41));
+
+        ppu.rerender_current_frame();
+        let frame = ppu.get_frame_buffer();
+        assert_eq!(frame[0], 3, "poked tile visible after rerender");
+        assert_eq!(frame[8 * SCREEN_WIDTH], 3, "every tile row redrawn");
+        assert_eq!(frame[SCREEN_WIDTH], 0, "non-top tile rows untouched");
+        assert_eq!(ppu.read_reg(0xFF44), ly, "LY unchanged");
+        assert_eq!(ppu.read_reg(0xFF41), stat, "mode/coincidence unchanged");
+    }
+
+    #[test]
+    fn mode_3_blocks_vram_unless_lenient() {
+        let mut ppu = Ppu::new();
+        ppu.step(MODE2_END + 1); // into mode 3 on line 0
+        assert_eq!(ppu.read_reg(0xFF41) & 0x03, 3);
+
+        ppu.write_vram(0x8000, 0x42);
+        assert_eq!(ppu.vram[0], 0x00, "strict mode drops the write");
+        assert_eq!(ppu.read_vram(0x8000), 0xFF);
+
+        ppu.set_strict_vram_access(false);
+        ppu.write_vram(0x8000, 0x42);
+        assert_eq!(ppu.read_vram(0x8000), 0x42, "lenient write lands");
+    }
+
+    #[test]
+    fn oam_is_blocked_during_oam_search() {
+        let mut ppu = Ppu::new();
+        ppu.oam[0] = 0x99;
+        ppu.step(1); // mode 2
+        assert_eq!(ppu.read_oam(0xFE00), 0xFF);
+        ppu.step(MODE3_END); // on into mode 0
+        assert_eq!(ppu.read_oam(0xFE00), 0x99);
+    }
+
+    #[test]
+    fn lcd_off_holds_ly_at_zero() {
+        let mut ppu = Ppu::new();
+        ppu.write_reg(0xFF40, 0x00);
+        ppu.step(DOTS_PER_LINE * 10);
+        assert_eq!(ppu.ly(), 0);
+    }
+}
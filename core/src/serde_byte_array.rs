@@ -0,0 +1,23 @@
+//! `#[serde(with = ...)]` helpers for fixed-size byte arrays; serde's own
+//! derive support stops at length 32. Written for the bincode wire format,
+//! where `serialize_bytes` and a `Vec<u8>` sequence encode identically.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer, const N: usize>(
+    bytes: &[u8; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(bytes)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    let bytes = Vec::<u8>::deserialize(deserializer)?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| D::Error::invalid_length(len, &"a byte array of the field's length"))
+}
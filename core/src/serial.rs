@@ -0,0 +1,147 @@
+//! Serial port (0xFF01/0xFF02). Transfers with the internal clock shift one
+//! bit per [`CYCLES_PER_BIT`] T-cycles (8192 Hz) and raise the Serial
+//! interrupt on completion; instant mode skips the delay for fast headless
+//! testing. Sent bytes are captured in [`Serial::output`] for test harnesses.
+
+/// T-cycles per shifted bit with the internal 8192 Hz clock.
+pub const CYCLES_PER_BIT: usize = 512;
+
+/// T-cycles for a full 8-bit transfer driven by the internal clock.
+pub const TRANSFER_CYCLES: usize = CYCLES_PER_BIT * 8;
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Serial {
+    sb: u8,
+    sc: u8,
+    /// Complete transfers immediately on the SC write instead of after
+    /// [`TRANSFER_CYCLES`].
+    instant: bool,
+    /// The byte being shifted out, captured when the transfer starts.
+    outgoing: u8,
+    /// Bits left to shift in the in-flight transfer; 0 means idle.
+    bits_remaining: u8,
+    /// T-cycles until the next bit shifts.
+    bit_counter: usize,
+    /// Completion seen since the last [`Serial::step`]; reported as the
+    /// interrupt request.
+    irq_pending: bool,
+    /// Every byte "sent" over the link so far.
+    pub output: Vec<u8>,
+}
+
+impl Serial {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Toggle instant-transfer mode (default: cycle-accurate).
+    pub fn set_instant(&mut self, on: bool) {
+        self.instant = on;
+    }
+
+    #[must_use]
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF01 => self.sb,
+            0xFF02 => self.sc | 0x7E,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF01 => self.sb = value,
+            0xFF02 => {
+                self.sc = value;
+                if value & 0x81 == 0x81 {
+                    self.outgoing = self.sb;
+                    if self.instant {
+                        self.sb = 0xFF;
+                        self.complete_transfer();
+                    } else {
+                        // Writing SC mid-transfer restarts the shift.
+                        self.bits_remaining = 8;
+                        self.bit_counter = CYCLES_PER_BIT;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Advance an in-flight transfer by `cycles` T-cycles. Returns true when
+    /// a transfer completed and the Serial interrupt should be requested.
+    pub fn step(&mut self, mut cycles: usize) -> bool {
+        while self.bits_remaining > 0 && cycles > 0 {
+            let consumed = cycles.min(self.bit_counter);
+            self.bit_counter -= consumed;
+            cycles -= consumed;
+            if self.bit_counter == 0 {
+                // No link partner: shift the MSB out and a 1 in.
+                self.sb = self.sb << 1 | 1;
+                self.bits_remaining -= 1;
+                if self.bits_remaining == 0 {
+                    self.complete_transfer();
+                } else {
+                    self.bit_counter = CYCLES_PER_BIT;
+                }
+            }
+        }
+        std::mem::take(&mut self.irq_pending)
+    }
+
+    fn complete_transfer(&mut self) {
+        self.output.push(self.outgoing);
+        self.sc &= 0x7F;
+        self.irq_pending = true;
+    }
+
+    /// Replace SB with the byte a link peer shifted back in. Used by the
+    /// MMU's serial callback after a transfer completes.
+    pub(crate) fn set_incoming(&mut self, byte: u8) {
+        self.sb = byte;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accurate_transfer_takes_transfer_cycles() {
+        let mut serial = Serial::new();
+        serial.write(0xFF01, b'P');
+        serial.write(0xFF02, 0x81);
+
+        assert!(!serial.step(TRANSFER_CYCLES - 4));
+        assert!(serial.output.is_empty());
+        assert_eq!(serial.read(0xFF02) & 0x80, 0x80);
+
+        assert!(serial.step(4));
+        assert_eq!(serial.output, vec![b'P']);
+        assert_eq!(serial.read(0xFF01), 0xFF);
+        assert_eq!(serial.read(0xFF02) & 0x80, 0);
+    }
+
+    #[test]
+    fn bits_shift_in_one_at_a_time() {
+        let mut serial = Serial::new();
+        serial.write(0xFF01, 0x00);
+        serial.write(0xFF02, 0x81);
+        assert!(!serial.step(CYCLES_PER_BIT * 3));
+        assert_eq!(serial.read(0xFF01), 0b0000_0111, "three 1-bits shifted in");
+    }
+
+    #[test]
+    fn instant_mode_completes_within_the_write() {
+        let mut serial = Serial::new();
+        serial.set_instant(true);
+        serial.write(0xFF01, b'P');
+        serial.write(0xFF02, 0x81);
+        assert_eq!(serial.output, vec![b'P']);
+        assert_eq!(serial.read(0xFF02) & 0x80, 0);
+        assert!(serial.step(0));
+    }
+}
@@ -0,0 +1,212 @@
+//! Top-level wiring of the CPU, MMU and APU into a steppable system.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::cartridge::Cartridge;
+use crate::cpu::Cpu;
+use crate::interrupts::Interrupt;
+use crate::joypad::Button;
+use crate::mmu::Mmu;
+use crate::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Cap on buffered frames while recording (~10 s at 59.7 Hz).
+const MAX_RECORDED_FRAMES: usize = 600;
+
+/// In-flight video capture: raw shade frames, encoded on stop.
+struct Recording {
+    dir: PathBuf,
+    frames: Vec<Box<[u8]>>,
+}
+
+/// A complete emulated Game Boy.
+pub struct System {
+    pub cpu: Cpu,
+    pub mmu: Mmu,
+    speed: u32,
+    recording: Option<Recording>,
+    frames_seen: u64,
+}
+
+impl System {
+    /// Build a system around a cartridge, in the post-boot-ROM state.
+    #[must_use]
+    pub fn new(cart: Cartridge) -> Self {
+        let mut cpu = Cpu::new();
+        cpu.reset_post_boot();
+        Self {
+            cpu,
+            mmu: Mmu::new(cart),
+            speed: 1,
+            recording: None,
+            frames_seen: 0,
+        }
+    }
+
+    /// Assemble a system from an already-configured CPU and MMU, e.g. one
+    /// starting inside a mapped boot ROM rather than post-boot.
+    #[must_use]
+    pub fn from_parts(cpu: Cpu, mmu: Mmu) -> Self {
+        Self {
+            cpu,
+            mmu,
+            speed: 1,
+            recording: None,
+            frames_seen: 0,
+        }
+    }
+
+    /// Execute one CPU instruction and advance the rest of the system by the
+    /// cycles it consumed. Returns the cycle count.
+    pub fn step(&mut self) -> Result<usize> {
+        let cycles = self.cpu.step(&mut self.mmu)?;
+        self.mmu.step(cycles)?;
+        self.capture_completed_frame();
+        Ok(cycles)
+    }
+
+    /// Run until the PPU finishes its next frame and return the completed
+    /// 160×144 shade framebuffer. Headless harnesses and screenshot-based
+    /// regression tests share this loop instead of reimplementing stepping.
+    pub fn run_frame(&mut self) -> Result<&[u8]> {
+        let target = self.mmu.frames_rendered() + 1;
+        while self.mmu.frames_rendered() < target {
+            self.step()?;
+        }
+        Ok(self.mmu.frame_buffer())
+    }
+
+    /// Press `button`, run exactly one frame, then release it. Returns the
+    /// frame rendered while the button was held, for scripted input.
+    pub fn tap_button(&mut self, button: Button) -> Result<&[u8]> {
+        self.mmu.set_button(button, true);
+        let target = self.mmu.frames_rendered() + 1;
+        while self.mmu.frames_rendered() < target {
+            self.step()?;
+        }
+        self.mmu.set_button(button, false);
+        Ok(self.mmu.frame_buffer())
+    }
+
+    /// Begin capturing frames into `dir`, encoded as a PNG sequence on stop.
+    pub fn start_recording(&mut self, dir: impl Into<PathBuf>) -> Result<()> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create recording dir {}", dir.display()))?;
+        self.recording = Some(Recording {
+            dir,
+            frames: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Stop recording and write `frame_00000.png`… into the recording
+    /// directory. Returns the number of frames written.
+    pub fn stop_recording(&mut self) -> Result<usize> {
+        let Some(recording) = self.recording.take() else {
+            return Ok(0);
+        };
+        for (index, shades) in recording.frames.iter().enumerate() {
+            let path = recording.dir.join(format!("frame_{index:05}.png"));
+            write_frame_png(&path, shades)?;
+        }
+        Ok(recording.frames.len())
+    }
+
+    /// Buffer the framebuffer whenever the PPU finished a frame this step.
+    fn capture_completed_frame(&mut self) {
+        let rendered = self.mmu.frames_rendered();
+        if rendered == self.frames_seen {
+            return;
+        }
+        self.frames_seen = rendered;
+        if let Some(recording) = &mut self.recording {
+            if recording.frames.len() < MAX_RECORDED_FRAMES {
+                recording
+                    .frames
+                    .push(self.mmu.frame_buffer().to_vec().into_boxed_slice());
+            }
+        }
+    }
+
+    /// Inject an interrupt request as if a peripheral had raised it. Lets
+    /// tests exercise e.g. LcdStat handling without running the PPU to the
+    /// matching scanline.
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.mmu.request_interrupt(interrupt);
+    }
+
+    /// Emulation speed multiplier used by frontends for fast-forward. The
+    /// APU is kept in sync so turbo mode can hold audio pitch constant.
+    pub fn set_speed(&mut self, multiplier: u32) {
+        self.speed = multiplier.max(1);
+        self.mmu.apu.set_speed(self.speed);
+    }
+
+    /// Toggle audio turbo mode for fast-forward.
+    pub fn set_turbo(&mut self, on: bool) {
+        self.mmu.apu.set_turbo(on);
+    }
+
+    #[must_use]
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+}
+
+/// Encode one shade frame as an RGB PNG.
+fn write_frame_png(path: &std::path::Path, shades: &[u8]) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("failed to create frame file {}", path.display()))?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        SCREEN_WIDTH as u32,
+        SCREEN_HEIGHT as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+
+    let mut data = Vec::with_capacity(shades.len() * 3);
+    for &shade in shades {
+        let color = common::Color::from_dmg_shade(shade);
+        data.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_writes_png_sequence_with_screen_dimensions() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100] = 0x18; // JR -2
+        rom[0x101] = 0xFE;
+        let mut system = System::new(Cartridge::new(rom).unwrap());
+
+        let dir = std::env::temp_dir().join(format!("gboxide-rec-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        system.start_recording(&dir).unwrap();
+        while system.mmu.frames_rendered() < 3 {
+            system.step().unwrap();
+        }
+        let written = system.stop_recording().unwrap();
+        assert_eq!(written, 3);
+
+        for index in 0..written {
+            let path = dir.join(format!("frame_{index:05}.png"));
+            let decoder = png::Decoder::new(File::open(&path).unwrap());
+            let reader = decoder.read_info().unwrap();
+            let info = reader.info();
+            assert_eq!(info.width, SCREEN_WIDTH as u32);
+            assert_eq!(info.height, SCREEN_HEIGHT as u32);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+//! DIV/TIMA timer registers (0xFF04–0xFF07).
+//!
+//! TIMA increments on the falling edge of a selected bit of the internal
+//! 16-bit divider, gated by the TAC enable bit. That single rule also
+//! produces the hardware quirks: a DIV write or TAC change that drops the
+//! selected bit increments TIMA, and an overflow reloads TMA only after a
+//! 4-cycle delay during which TIMA reads as zero.
+
+/// The divider and timer counter block.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timer {
+    /// Internal 16-bit divider; DIV is its high byte.
+    divider: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    /// Cycles until the delayed TMA reload lands; 0 means none pending.
+    overflow_delay: u8,
+    /// CGB double-speed (KEY1): the divider ticks twice per emulated
+    /// T-cycle, doubling every DIV-derived rate.
+    double_speed: bool,
+}
+
+impl Timer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF04 => (self.divider >> 8) as u8,
+            0xFF05 => self.tima,
+            0xFF06 => self.tma,
+            0xFF07 => self.tac | 0xF8,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF04 => self.reset_div(),
+            0xFF05 => {
+                // A TIMA write during the overflow delay cancels the reload.
+                self.overflow_delay = 0;
+                self.tima = value;
+            }
+            0xFF06 => self.tma = value,
+            0xFF07 => {
+                let before = self.selected_bit();
+                self.tac = value & 0x07;
+                if before && !self.selected_bit() {
+                    self.increment_tima();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Switch the CGB double-speed mode on or off. Nothing toggles this
+    /// until KEY1 itself is emulated; the default is single speed.
+    pub fn set_double_speed(&mut self, on: bool) {
+        self.double_speed = on;
+    }
+
+    /// Advance by `cycles` T-cycles. Returns `true` when TIMA overflowed and
+    /// a Timer interrupt should be requested.
+    pub fn step(&mut self, cycles: usize) -> bool {
+        let ticks = if self.double_speed {
+            cycles * 2
+        } else {
+            cycles
+        };
+        let mut interrupt = false;
+        for _ in 0..ticks {
+            if self.overflow_delay > 0 {
+                self.overflow_delay -= 1;
+                if self.overflow_delay == 0 {
+                    self.tima = self.tma;
+                    interrupt = true;
+                }
+            }
+            let before = self.selected_bit();
+            self.divider = self.divider.wrapping_add(1);
+            if before && !self.selected_bit() {
+                self.increment_tima();
+            }
+        }
+        interrupt
+    }
+
+    /// The divider bit TAC selects, ANDed with the enable bit. TIMA clocks
+    /// on this signal's falling edge.
+    fn selected_bit(&self) -> bool {
+        let bit = match self.tac & 0x03 {
+            0x00 => 9,
+            0x01 => 3,
+            0x02 => 5,
+            _ => 7,
+        };
+        self.tac & 0x04 != 0 && (self.divider >> bit) & 1 != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (tima, overflow) = self.tima.overflowing_add(1);
+        // On overflow TIMA reads 0 until the delayed reload 4 cycles later.
+        self.tima = tima;
+        if overflow {
+            self.overflow_delay = 4;
+        }
+    }
+
+    /// Zero the internal divider, as a DIV write or STOP does. Dropping the
+    /// selected bit while high is a falling edge, which clocks TIMA.
+    pub fn reset_div(&mut self) {
+        let before = self.selected_bit();
+        self.divider = 0;
+        if before {
+            self.increment_tima();
+        }
+    }
+
+    /// The full 16-bit internal divider (DIV is its high byte). Peripherals
+    /// that clock off divider bits — the APU frame sequencer — read this.
+    #[must_use]
+    pub fn internal_div(&self) -> u16 {
+        self.divider
+    }
+
+    /// Snapshot of (DIV, TIMA, TMA, TAC) for the test harness.
+    #[must_use]
+    pub fn get_state(&self) -> (u8, u8, u8, u8) {
+        ((self.divider >> 8) as u8, self.tima, self.tma, self.tac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn div_increments_every_256_cycles() {
+        let mut timer = Timer::new();
+        timer.step(255);
+        assert_eq!(timer.read(0xFF04), 0);
+        timer.step(1);
+        assert_eq!(timer.read(0xFF04), 1);
+    }
+
+    #[test]
+    fn div_write_resets() {
+        let mut timer = Timer::new();
+        timer.step(512);
+        timer.write(0xFF04, 0x55);
+        assert_eq!(timer.read(0xFF04), 0);
+    }
+
+    #[test]
+    fn double_speed_doubles_the_divider_rate_for_the_same_budget() {
+        let mut timer = Timer::new();
+        timer.step(256);
+        assert_eq!(timer.read(0xFF04), 1);
+
+        timer.set_double_speed(true);
+        timer.step(256);
+        assert_eq!(timer.read(0xFF04), 3, "two DIV ticks in the same budget");
+
+        timer.set_double_speed(false);
+        timer.step(256);
+        assert_eq!(timer.read(0xFF04), 4);
+    }
+
+    #[test]
+    fn internal_div_exposes_the_low_bits() {
+        let mut timer = Timer::new();
+        timer.step(0x1234);
+        assert_eq!(timer.internal_div(), 0x1234);
+        assert_eq!(timer.read(0xFF04), 0x12);
+    }
+
+    #[test]
+    fn tima_overflow_reloads_tma_after_four_cycles() {
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05); // enabled, /16 (divider bit 3)
+        timer.write(0xFF06, 0x23);
+        timer.write(0xFF05, 0xFF);
+
+        // Bit 3 falls at divider 16: the overflow happens, but the reload
+        // and interrupt are 4 cycles late and TIMA reads 0 meanwhile.
+        assert!(!timer.step(16));
+        assert_eq!(timer.read(0xFF05), 0x00);
+        assert!(timer.step(4));
+        assert_eq!(timer.read(0xFF05), 0x23);
+    }
+
+    #[test]
+    fn tima_write_during_overflow_delay_cancels_the_reload() {
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05);
+        timer.write(0xFF06, 0x23);
+        timer.write(0xFF05, 0xFF);
+        timer.step(16); // overflow; reload pending
+
+        timer.write(0xFF05, 0x80);
+        assert!(!timer.step(8), "cancelled reload must not interrupt");
+        assert_eq!(timer.read(0xFF05), 0x80);
+    }
+
+    #[test]
+    fn div_write_with_selected_bit_high_clocks_tima() {
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05); // /16: divider bit 3
+        timer.step(8); // bit 3 now high
+        timer.write(0xFF04, 0x00);
+        assert_eq!(timer.read(0xFF05), 1, "reset edge increments TIMA");
+    }
+
+    #[test]
+    fn disabling_the_timer_with_the_bit_high_clocks_tima() {
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05);
+        timer.step(8); // bit 3 high
+        timer.write(0xFF07, 0x00); // disable: selected signal falls
+        assert_eq!(timer.read(0xFF05), 1);
+        timer.step(4096);
+        assert_eq!(timer.read(0xFF05), 1, "disabled timer stays put");
+    }
+
+    #[test]
+    fn disabled_timer_does_not_tick() {
+        let mut timer = Timer::new();
+        timer.write(0xFF05, 0x10);
+        timer.step(4096);
+        assert_eq!(timer.read(0xFF05), 0x10);
+    }
+}
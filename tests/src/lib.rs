@@ -0,0 +1,21 @@
+//! Helpers for building in-memory test ROMs.
+
+/// A 32 KB ROM-only cartridge image with `program` placed at 0x0100.
+#[must_use]
+pub fn rom_with_program(program: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x100..0x100 + program.len()].copy_from_slice(program);
+    rom
+}
+
+/// Like [`rom_with_program`], but also places handler bytes at interrupt
+/// vectors (e.g. 0x0050 for Timer).
+#[must_use]
+pub fn rom_with_vectors(program: &[u8], vectors: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut rom = rom_with_program(program);
+    for (addr, bytes) in vectors {
+        let addr = *addr as usize;
+        rom[addr..addr + bytes.len()].copy_from_slice(bytes);
+    }
+    rom
+}
@@ -0,0 +1,58 @@
+//! ADD SP,e and LD HL,SP+e: half/carry from bits 3/7 of the unsigned
+//! offset byte, Z and N always clear.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+fn run(program: &[u8], sp: u16, steps: usize) -> System {
+    let mut system = System::new(Cartridge::new(rom_with_program(program)).unwrap());
+    system.cpu.regs.sp = sp;
+    for _ in 0..steps {
+        system.step().unwrap();
+    }
+    system
+}
+
+fn assert_flags(system: &System, h: bool, c: bool) {
+    assert!(!system.cpu.regs.flag_z(), "Z always clears");
+    assert!(!system.cpu.regs.flag_n(), "N always clears");
+    assert_eq!(system.cpu.regs.flag_h(), h);
+    assert_eq!(system.cpu.regs.flag_c(), c);
+}
+
+#[test]
+fn add_sp_positive_offset_carries_from_bits_3_and_7() {
+    // 0x0FF8 + 0x08: low nibble 8+8 and low byte 0xF8+0x08 both carry.
+    let system = run(&[0xE8, 0x08], 0x0FF8, 1);
+    assert_eq!(system.cpu.regs.sp, 0x1000);
+    assert_flags(&system, true, true);
+}
+
+#[test]
+fn add_sp_negative_offset_uses_the_unsigned_byte() {
+    // e = -1 is byte 0xFF: SP 0xFFFF has low nibble/byte 0xF/0xFF, so both
+    // bit-3 and bit-7 carries fire even though SP decreases.
+    let system = run(&[0xE8, 0xFF], 0xFFFF, 1);
+    assert_eq!(system.cpu.regs.sp, 0xFFFE);
+    assert_flags(&system, true, true);
+}
+
+#[test]
+fn add_sp_negative_offset_without_low_carries() {
+    // SP 0xD000 + (-1): 0x00 + 0xFF produces no carry out of bit 3 or 7.
+    let system = run(&[0xE8, 0xFF], 0xD000, 1);
+    assert_eq!(system.cpu.regs.sp, 0xCFFF);
+    assert_flags(&system, false, false);
+}
+
+#[test]
+fn ld_hl_sp_e_matches_add_sp_flags_and_leaves_sp_alone() {
+    let system = run(&[0xF8, 0x02], 0xFFFE, 1);
+    assert_eq!(system.cpu.regs.hl(), 0x0000);
+    assert_eq!(system.cpu.regs.sp, 0xFFFE, "SP is not written");
+    assert_flags(&system, true, true);
+
+    let system = run(&[0xF8, 0xFE], 0xC001, 1); // e = -2
+    assert_eq!(system.cpu.regs.hl(), 0xBFFF);
+    assert_flags(&system, false, false);
+}
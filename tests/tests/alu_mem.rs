@@ -0,0 +1,57 @@
+//! ADC A,(HL) and SBC A,(HL) must read through the bus, honour carry-in and
+//! set the half-carry from bit 3.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+fn run(program: &[u8], steps: usize) -> System {
+    let mut system = System::new(Cartridge::new(rom_with_program(program)).unwrap());
+    for _ in 0..steps {
+        system.step().unwrap();
+    }
+    system
+}
+
+#[test]
+fn adc_a_hl_uses_memory_and_carry_in() {
+    let mut system = run(
+        &[
+            0x21, 0x00, 0xC0, // LD HL,0xC000
+            0x36, 0x0F, // LD (HL),0x0F
+            0x3E, 0x01, // LD A,0x01
+            0xB7, // OR A (clear carry)
+            0x37, // SCF (carry-in = 1)
+            0x8E, // ADC A,(HL)
+        ],
+        5,
+    );
+    let cycles = system.step().unwrap(); // ADC A,(HL)
+    assert_eq!(cycles, 8, "ADC A,(HL) must cost 8 cycles");
+    // 0x01 + 0x0F + 1 = 0x11 with half-carry, no carry.
+    assert_eq!(system.cpu.regs.a, 0x11);
+    assert!(system.cpu.regs.flag_h());
+    assert!(!system.cpu.regs.flag_c());
+    assert!(!system.cpu.regs.flag_z());
+}
+
+#[test]
+fn sbc_a_hl_uses_memory_and_carry_in() {
+    let mut system = run(
+        &[
+            0x21, 0x00, 0xC0, // LD HL,0xC000
+            0x36, 0x01, // LD (HL),0x01
+            0x3E, 0x10, // LD A,0x10
+            0xB7, // OR A (clear carry)
+            0x37, // SCF (carry-in = 1)
+            0x9E, // SBC A,(HL)
+        ],
+        5,
+    );
+    let cycles = system.step().unwrap(); // SBC A,(HL)
+    assert_eq!(cycles, 8, "SBC A,(HL) must cost 8 cycles");
+    // 0x10 - 0x01 - 1 = 0x0E with half-borrow from bit 4.
+    assert_eq!(system.cpu.regs.a, 0x0E);
+    assert!(system.cpu.regs.flag_n());
+    assert!(system.cpu.regs.flag_h());
+    assert!(!system.cpu.regs.flag_c());
+}
@@ -0,0 +1,47 @@
+//! Boot ROM mapping and the post-boot register snapshot.
+
+use core_lib::{Cartridge, Cpu, Mmu};
+use tests::rom_with_program;
+
+#[test]
+fn boot_rom_overlays_until_ff50_unmaps_it() {
+    let mut rom = rom_with_program(&[]);
+    rom[0x0000] = 0xAA;
+    rom[0x00FF] = 0xBB;
+    let mut boot = vec![0u8; 0x100];
+    boot[0x0000] = 0x11;
+    boot[0x00FF] = 0x22;
+
+    let mut mmu = Mmu::with_boot_rom(Cartridge::new(rom).unwrap(), boot);
+    assert!(mmu.boot_rom_mapped());
+    assert_eq!(mmu.read(0x0000), 0x11);
+    assert_eq!(mmu.read(0x00FF), 0x22);
+    // The overlay ends at 0x0100: that byte is always cartridge.
+    assert_eq!(mmu.read(0x0100), 0x00);
+
+    // Writing zero to 0xFF50 does nothing.
+    mmu.write(0xFF50, 0x00);
+    assert!(mmu.boot_rom_mapped());
+
+    mmu.write(0xFF50, 0x01);
+    assert!(!mmu.boot_rom_mapped());
+    assert_eq!(mmu.read(0x0000), 0xAA);
+    assert_eq!(mmu.read(0x00FF), 0xBB);
+
+    // The unmap is one-way: another write cannot bring the boot ROM back.
+    mmu.write(0xFF50, 0x00);
+    assert!(!mmu.boot_rom_mapped());
+    assert_eq!(mmu.read(0x0000), 0xAA);
+}
+
+#[test]
+fn new_post_boot_matches_the_canonical_dmg_snapshot() {
+    let cpu = Cpu::new_post_boot();
+    assert_eq!(cpu.regs.af(), 0x01B0);
+    assert_eq!(cpu.regs.bc(), 0x0013);
+    assert_eq!(cpu.regs.de(), 0x00D8);
+    assert_eq!(cpu.regs.hl(), 0x014D);
+    assert_eq!(cpu.regs.sp, 0xFFFE);
+    assert_eq!(cpu.regs.pc, 0x0100);
+    assert!(!cpu.ime());
+}
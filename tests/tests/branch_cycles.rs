@@ -0,0 +1,48 @@
+//! Exact cycle counts for conditional branches, taken and not taken.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+fn system_running(program: &[u8]) -> System {
+    let rom = rom_with_program(program);
+    System::new(Cartridge::new(rom).unwrap())
+}
+
+#[test]
+fn jr_cc_cycles() {
+    // Z is set from the post-boot F=0xB0, so JR NZ is not taken and JR Z is.
+    let mut system = system_running(&[
+        0x20, 0x02, // JR NZ,+2  (not taken: 8)
+        0x28, 0x02, // JR Z,+2   (taken: 12)
+    ]);
+    assert_eq!(system.step().unwrap(), 8);
+    assert_eq!(system.step().unwrap(), 12);
+}
+
+#[test]
+fn jp_cc_cycles() {
+    let mut system = system_running(&[
+        0xC2, 0x06, 0x01, // JP NZ,0x0106 (not taken: 12)
+        0xCA, 0x08, 0x01, // JP Z,0x0108  (taken: 16)
+    ]);
+    assert_eq!(system.step().unwrap(), 12);
+    assert_eq!(system.step().unwrap(), 16);
+}
+
+#[test]
+fn call_and_ret_cc_cycles() {
+    let mut system = system_running(&[
+        0xC4, 0x00, 0x02, // CALL NZ,0x0200 (not taken: 12)
+        0xCC, 0x0A, 0x01, // CALL Z,0x010A  (taken: 24)
+        0x00, // 0x0106: NOP
+        0x00, 0x00, 0x00, // padding
+        0xC0, // 0x010A: RET NZ (not taken: 8)
+        0xC8, // 0x010B: RET Z  (taken: 20)
+    ]);
+    assert_eq!(system.step().unwrap(), 12);
+    assert_eq!(system.step().unwrap(), 24);
+    assert_eq!(system.cpu.regs.pc, 0x010A);
+    assert_eq!(system.step().unwrap(), 8);
+    assert_eq!(system.step().unwrap(), 20);
+    assert_eq!(system.cpu.regs.pc, 0x0106);
+}
@@ -0,0 +1,32 @@
+//! Typed `CpuError` values from `Cpu::step`.
+
+use core_lib::{Cartridge, Cpu, CpuError, Mmu};
+use tests::rom_with_program;
+
+#[test]
+fn unknown_opcodes_surface_a_typed_error() {
+    // 0xD3 is not an SM83 instruction.
+    let mut cpu = Cpu::new_post_boot();
+    let mut mmu = Mmu::new(Cartridge::new(rom_with_program(&[0xD3])).unwrap());
+
+    match cpu.step(&mut mmu) {
+        Err(CpuError::UnimplementedOpcode { opcode, pc }) => {
+            assert_eq!(opcode, 0xD3);
+            assert_eq!(pc, 0x0100);
+        }
+        other => panic!("expected a typed unimplemented-opcode error, got {other:?}"),
+    }
+}
+
+#[test]
+fn cpu_errors_format_and_convert_for_anyhow_callers() {
+    let err = CpuError::UnimplementedOpcode {
+        opcode: 0xD3,
+        pc: 0x0100,
+    };
+    assert_eq!(err.to_string(), "unimplemented opcode 0xD3 at 0x0100");
+
+    // `?` in anyhow contexts keeps working, with the same message.
+    let as_anyhow: anyhow::Error = err.into();
+    assert!(as_anyhow.to_string().contains("0xD3"));
+}
@@ -0,0 +1,86 @@
+//! DAA, SCF and CCF flag handling, swept against a reference model.
+
+use core_lib::cpu::registers::{FLAG_C, FLAG_H, FLAG_N};
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+/// Reference DAA: N picks add vs subtract, H/C are the binary carries from
+/// the preceding arithmetic, C comes out set only on a decimal carry.
+fn reference_daa(a: u8, n: bool, h: bool, c: bool) -> (u8, bool) {
+    let mut adjust = 0u8;
+    let mut carry = c;
+    if n {
+        if h {
+            adjust |= 0x06;
+        }
+        if c {
+            adjust |= 0x60;
+        }
+        (a.wrapping_sub(adjust), carry)
+    } else {
+        if h || a & 0x0F > 0x09 {
+            adjust |= 0x06;
+        }
+        if c || a > 0x99 {
+            adjust |= 0x60;
+            carry = true;
+        }
+        (a.wrapping_add(adjust), carry)
+    }
+}
+
+fn system_with(opcode: u8) -> System {
+    System::new(Cartridge::new(rom_with_program(&[opcode])).unwrap())
+}
+
+#[test]
+fn daa_sweeps_a_and_flags_against_the_reference() {
+    for a in 0u16..=0xFF {
+        for flags in 0u8..8 {
+            let n = flags & 0x04 != 0;
+            let h = flags & 0x02 != 0;
+            let c = flags & 0x01 != 0;
+
+            let mut system = system_with(0x27);
+            system.cpu.regs.a = a as u8;
+            system.cpu.regs.set_flag(FLAG_N, n);
+            system.cpu.regs.set_flag(FLAG_H, h);
+            system.cpu.regs.set_flag(FLAG_C, c);
+            system.step().unwrap();
+
+            let (expected_a, expected_c) = reference_daa(a as u8, n, h, c);
+            let context = format!("A={a:#04X} N={n} H={h} C={c}");
+            assert_eq!(system.cpu.regs.a, expected_a, "{context}");
+            assert_eq!(system.cpu.regs.flag_c(), expected_c, "{context}");
+            assert_eq!(system.cpu.regs.flag_z(), expected_a == 0, "{context}");
+            assert!(!system.cpu.regs.flag_h(), "H always clears: {context}");
+            assert_eq!(system.cpu.regs.flag_n(), n, "N passes through: {context}");
+        }
+    }
+}
+
+#[test]
+fn scf_sets_carry_and_clears_n_and_h() {
+    let mut system = system_with(0x37);
+    system.cpu.regs.set_flag(FLAG_N, true);
+    system.cpu.regs.set_flag(FLAG_H, true);
+    system.cpu.regs.set_flag(FLAG_C, false);
+    system.step().unwrap();
+    assert!(system.cpu.regs.flag_c());
+    assert!(!system.cpu.regs.flag_n());
+    assert!(!system.cpu.regs.flag_h());
+}
+
+#[test]
+fn ccf_toggles_carry_and_clears_n_and_h() {
+    for carry_in in [false, true] {
+        let mut system = system_with(0x3F);
+        system.cpu.regs.set_flag(FLAG_N, true);
+        system.cpu.regs.set_flag(FLAG_H, true);
+        system.cpu.regs.set_flag(FLAG_C, carry_in);
+        system.step().unwrap();
+        assert_eq!(system.cpu.regs.flag_c(), !carry_in);
+        assert!(!system.cpu.regs.flag_n());
+        assert!(!system.cpu.regs.flag_h());
+    }
+}
@@ -0,0 +1,113 @@
+//! Determinism: two runs of the same ROM with the same input schedule must
+//! end in identical state. Netplay and replays depend on this holding.
+
+use core_lib::joypad::Button;
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+/// Button changes to apply, keyed by instruction-step index.
+type ReplayInput = [(usize, Button, bool)];
+
+/// Everything observable about a finished run.
+struct RunResult {
+    frame: Vec<u8>,
+    serial: Vec<u8>,
+    regs: String,
+    cycles: usize,
+}
+
+/// Run the ROM for `steps` instructions, replaying `inputs` along the way.
+fn run(rom: Vec<u8>, steps: usize, inputs: &ReplayInput) -> RunResult {
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    // The loop restarts the transfer faster than the 8192 Hz clock finishes
+    // one, so complete transfers instantly to get bytes out.
+    system.mmu.set_serial_instant(true);
+    let mut cycles = 0;
+    for step in 0..steps {
+        for (at, button, pressed) in inputs {
+            if *at == step {
+                system.mmu.set_button(*button, *pressed);
+            }
+        }
+        cycles += system.step().unwrap();
+    }
+    RunResult {
+        frame: system.mmu.ppu.get_frame_buffer().to_vec(),
+        serial: system.mmu.serial.output.clone(),
+        regs: format!("{:?}", system.cpu.regs),
+        cycles,
+    }
+}
+
+/// Reads the joypad, mixes it into a running sum, ships the sum over serial
+/// and scribbles it into VRAM so the framebuffer depends on the inputs too.
+fn replay_rom() -> Vec<u8> {
+    rom_with_program(&[
+        0x3E, 0x20, // LD A,0x20 (select direction keys)
+        0xE0, 0x00, // LDH (FF00),A
+        0x21, 0x00, 0x98, // LD HL,0x9800
+        // loop:
+        0xF0, 0x00, // LDH A,(FF00)
+        0x80, // ADD A,B
+        0x47, // LD B,A
+        0xE0, 0x01, // LDH (SB),A
+        0x3E, 0x81, // LD A,0x81
+        0xE0, 0x02, // LDH (SC),A  (start transfer)
+        0x78, // LD A,B
+        0x22, // LD (HL+),A
+        0x18, 0xF2, // JR loop
+    ])
+}
+
+#[test]
+fn identical_runs_produce_identical_state() {
+    let inputs: Vec<(usize, Button, bool)> = vec![
+        (500, Button::Right, true),
+        (4_000, Button::Right, false),
+        (9_000, Button::A, true),
+        (20_000, Button::A, false),
+        (33_000, Button::Down, true),
+    ];
+
+    let first = run(replay_rom(), 50_000, &inputs);
+    let second = run(replay_rom(), 50_000, &inputs);
+
+    assert_eq!(first.frame, second.frame, "framebuffers diverged");
+    assert_eq!(first.serial, second.serial, "serial output diverged");
+    assert_eq!(first.regs, second.regs, "register state diverged");
+    assert_eq!(first.cycles, second.cycles, "cycle counts diverged");
+    assert!(
+        !first.serial.is_empty(),
+        "the test ROM should actually transfer bytes"
+    );
+}
+
+#[test]
+fn interleaved_instances_match_an_isolated_run() {
+    // Two emulator instances stepped in lockstep must end up exactly where
+    // a single isolated run does — no shared state between CPU instances.
+    let isolated = run(replay_rom(), 10_000, &[]);
+
+    let mut a = System::new(Cartridge::new(replay_rom()).unwrap());
+    let mut b = System::new(Cartridge::new(replay_rom()).unwrap());
+    a.mmu.set_serial_instant(true);
+    b.mmu.set_serial_instant(true);
+    for _ in 0..10_000 {
+        a.step().unwrap();
+        b.step().unwrap();
+    }
+
+    for system in [&a, &b] {
+        assert_eq!(format!("{:?}", system.cpu.regs), isolated.regs);
+        assert_eq!(system.mmu.serial.output, isolated.serial);
+    }
+}
+
+#[test]
+fn diverging_inputs_are_observable() {
+    // Sanity check that the harness can tell runs apart at all: drop one
+    // press and the serial stream must change.
+    let pressed = run(replay_rom(), 50_000, &[(500, Button::Right, true)]);
+    let idle = run(replay_rom(), 50_000, &[]);
+    assert_ne!(pressed.serial, idle.serial);
+}
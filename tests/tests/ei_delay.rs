@@ -0,0 +1,94 @@
+//! EI enables IME only after the following instruction.
+
+use core_lib::interrupts::Interrupt;
+use core_lib::{Cartridge, System};
+use tests::{rom_with_program, rom_with_vectors};
+
+#[test]
+fn ei_di_leaves_interrupts_disabled() {
+    let rom = rom_with_program(&[
+        0xFB, // EI
+        0xF3, // DI cancels the scheduled enable
+        0x00, // NOP
+    ]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    system.step().unwrap(); // EI
+    system.step().unwrap(); // DI
+    system.step().unwrap(); // NOP
+    assert!(!system.cpu.ime());
+}
+
+#[test]
+fn di_ei_di_leaves_interrupts_disabled() {
+    let rom = rom_with_program(&[
+        0xF3, // DI
+        0xFB, // EI
+        0xF3, // DI lands inside the EI delay window
+        0x00, // NOP
+    ]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    for _ in 0..4 {
+        system.step().unwrap();
+    }
+    assert!(!system.cpu.ime());
+}
+
+#[test]
+fn ei_reti_enables_interrupts_immediately() {
+    let rom = rom_with_program(&[
+        0x31, 0xFE, 0xFF, // LD SP,$FFFE
+        0xCD, 0x08, 0x01, // CALL $0108
+        0x00, // NOP (return target)
+        0x00,
+        0xFB, // $0108: EI
+        0xD9, // RETI — enables IME with no delay
+    ]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    system.step().unwrap(); // LD SP
+    system.step().unwrap(); // CALL
+    system.step().unwrap(); // EI
+    system.step().unwrap(); // RETI
+    assert!(system.cpu.ime());
+    assert_eq!(system.cpu.regs.pc, 0x0106);
+}
+
+#[test]
+fn a_cb_prefixed_follower_also_ends_the_ei_delay() {
+    let rom = rom_with_program(&[
+        0xFB, // EI
+        0xCB, 0x37, // SWAP A — a CB-prefixed instruction as the follower
+        0x00, // NOP
+    ]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    system.step().unwrap(); // EI
+    assert!(!system.cpu.ime());
+    system.step().unwrap(); // SWAP A lands the scheduled enable
+    assert!(system.cpu.ime());
+}
+
+#[test]
+fn ei_nop_dispatches_only_after_the_nop() {
+    let program = [
+        0x3E, 0x04, // LD A,0x04 (Timer)
+        0xE0, 0xFF, // LDH (IE),A
+        0xFB, // EI
+        0x00, // NOP
+        0x00, // NOP
+    ];
+    let rom = rom_with_vectors(&program, &[(0x0050, &[0x76])]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    system.step().unwrap(); // LD A
+    system.step().unwrap(); // LDH (IE),A
+    system.request_interrupt(Interrupt::Timer);
+
+    system.step().unwrap(); // EI — IME still off
+    assert!(!system.cpu.ime());
+
+    system.step().unwrap(); // NOP executes; the interrupt must not preempt it
+    assert_ne!(system.cpu.regs.pc, 0x0050);
+    assert!(system.cpu.ime());
+
+    system.step().unwrap(); // dispatch
+    assert_eq!(system.cpu.regs.pc, 0x0050);
+}
@@ -0,0 +1,40 @@
+//! EI immediately before HALT must still let a pending interrupt wake the
+//! CPU into its handler rather than leaving it halted forever.
+
+use core_lib::interrupts::Interrupt;
+use core_lib::{Cartridge, System};
+use tests::rom_with_vectors;
+
+#[test]
+fn ei_halt_with_pending_interrupt_runs_the_isr() {
+    let program = [
+        0x3E, 0x04, // LD A,0x04 (Timer)
+        0xE0, 0xFF, // LDH (IE),A
+        0xFB, // EI
+        0x76, // HALT
+        0x00, // NOP (after wake + RETI-less handler we just check the vector)
+    ];
+    let rom = rom_with_vectors(&program, &[(0x0050, &[0x76])]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    // Run LD/LDH/EI/HALT.
+    for _ in 0..4 {
+        system.step().unwrap();
+    }
+    assert!(system.cpu.halted);
+
+    // Interrupt arrives while halted, with IME enabled via the EI just
+    // before HALT.
+    system.request_interrupt(Interrupt::Timer);
+
+    let mut reached_vector = false;
+    for _ in 0..10 {
+        system.step().unwrap();
+        if (0x0050..=0x0051).contains(&system.cpu.regs.pc) {
+            reached_vector = true;
+            break;
+        }
+    }
+    assert!(reached_vector, "CPU stayed halted instead of running the ISR");
+    assert!(!system.cpu.halted);
+}
@@ -0,0 +1,59 @@
+//! The interrupt-dispatch bug: IE is re-read between the two bytes of the
+//! PC push, so a stack at 0x0000 lets the high-byte push rewrite IE and
+//! redirect — or cancel — the dispatch.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_vectors;
+
+/// Point SP at 0x0000 (so the push's high byte lands on IE at 0xFFFF),
+/// EI, then spin at 0x0106. The PC pushed during dispatch is 0x0106, so
+/// the high-byte push rewrites IE to 0x01 (VBlank only).
+fn bug_rom() -> Vec<u8> {
+    let program = [
+        0x31, 0x00, 0x00, // LD SP,$0000
+        0xFB, // EI
+        0x00, // NOP (EI delay)
+        0x00, // NOP
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom = rom_with_vectors(&program, &[(0x0040, &[0x76]), (0x0050, &[0x76])]);
+    rom[0x0000] = 0x76; // HALT at the cancelled-dispatch landing spot
+    rom
+}
+
+fn system_with(ie: u8, iflag: u8) -> System {
+    let mut system = System::new(Cartridge::new(bug_rom()).unwrap());
+    for _ in 0..5 {
+        system.step().unwrap(); // LD SP / EI / NOPs; IME is now armed
+    }
+    system.mmu.write(0xFFFF, ie);
+    system.mmu.write(0xFF0F, iflag);
+    system
+}
+
+#[test]
+fn ie_clobber_during_the_push_redirects_the_vector() {
+    // Timer is the dispatching interrupt, but VBlank is also raised; the
+    // high-byte push rewrites IE to 0x01, so VBlank wins the re-read.
+    let mut system = system_with(0x04, 0x05);
+    let cycles = system.step().unwrap();
+    assert_eq!(cycles, 20, "dispatch takes 5 M-cycles");
+    assert_eq!(system.cpu.regs.pc, 0x0040, "redirected to VBlank");
+    assert_eq!(system.mmu.read(0xFFFF), 0x01, "IE holds the pushed byte");
+    assert_eq!(
+        system.mmu.read(0xFF0F) & 0x05,
+        0x04,
+        "VBlank's IF bit was consumed, Timer's was not"
+    );
+}
+
+#[test]
+fn ie_clobber_with_nothing_left_pending_cancels_to_0x0000() {
+    // Only Timer is raised; once the push rewrites IE to 0x01 nothing
+    // enabled is pending, so the dispatch collapses to vector 0x0000.
+    let mut system = system_with(0x04, 0x04);
+    let cycles = system.step().unwrap();
+    assert_eq!(cycles, 20);
+    assert_eq!(system.cpu.regs.pc, 0x0000, "cancelled dispatch lands at 0");
+    assert_eq!(system.mmu.read(0xFF0F) & 0x04, 0x04, "IF is left set");
+}
@@ -0,0 +1,35 @@
+//! Joypad interrupt integration test: a button press must vector to 0x0060.
+
+use core_lib::joypad::Button;
+use core_lib::{Cartridge, System};
+use tests::rom_with_vectors;
+
+#[test]
+fn button_press_vectors_to_0x60() {
+    let program = [
+        0x3E, 0x10, // LD A,0x10 (Joypad)
+        0xE0, 0xFF, // LDH (IE),A
+        0xFB, // EI
+        0x18, 0xFE, // JR -2
+    ];
+    let rom = rom_with_vectors(&program, &[(0x0060, &[0x76])]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    // Let the program set up IE/IME first.
+    for _ in 0..4 {
+        system.step().unwrap();
+    }
+    // The interrupt only fires for the selected group, so pick actions.
+    system.mmu.write(0xFF00, 0x10);
+    system.mmu.set_button(Button::A, true);
+
+    let mut reached_vector = false;
+    for _ in 0..100 {
+        system.step().unwrap();
+        if (0x0060..=0x0061).contains(&system.cpu.regs.pc) {
+            reached_vector = true;
+            break;
+        }
+    }
+    assert!(reached_vector, "joypad interrupt never vectored to 0x0060");
+}
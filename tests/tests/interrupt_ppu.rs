@@ -0,0 +1,54 @@
+//! PPU-sourced interrupt integration tests using synthetic injection,
+//! without running the PPU to the matching scanline.
+
+use core_lib::interrupts::Interrupt;
+use core_lib::{Cartridge, System};
+use tests::rom_with_vectors;
+
+fn system_with_ie(ie: u8, vectors: &[(u16, &[u8])]) -> System {
+    let program = [
+        0x3E, ie, // LD A,ie
+        0xE0, 0xFF, // LDH (IE),A
+        0xFB, // EI
+        0x18, 0xFE, // JR -2
+    ];
+    let rom = rom_with_vectors(&program, vectors);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+    // Let the program set up IE/IME first.
+    for _ in 0..4 {
+        system.step().unwrap();
+    }
+    system
+}
+
+#[test]
+fn injected_lcdstat_vectors_to_0x48() {
+    let mut system = system_with_ie(0x02, &[(0x0048, &[0x76])]);
+    system.request_interrupt(Interrupt::LcdStat);
+
+    let mut reached_vector = false;
+    for _ in 0..100 {
+        system.step().unwrap();
+        if (0x0048..=0x0049).contains(&system.cpu.regs.pc) {
+            reached_vector = true;
+            break;
+        }
+    }
+    assert!(reached_vector, "LcdStat interrupt never vectored to 0x0048");
+}
+
+#[test]
+fn injected_vblank_vectors_to_0x40() {
+    let mut system = system_with_ie(0x01, &[(0x0040, &[0x76])]);
+    system.request_interrupt(Interrupt::VBlank);
+
+    let mut reached_vector = false;
+    for _ in 0..100 {
+        system.step().unwrap();
+        if (0x0040..=0x0041).contains(&system.cpu.regs.pc) {
+            reached_vector = true;
+            break;
+        }
+    }
+    assert!(reached_vector, "VBlank interrupt never vectored to 0x0040");
+}
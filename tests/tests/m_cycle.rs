@@ -0,0 +1,63 @@
+//! `Cpu::step_m_cycle`: peripherals advance between the bus accesses of a
+//! single instruction.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+/// LD SP,0xFF05 then PUSH BC. With SP there, the PUSH's high-byte write
+/// lands on DIV (0xFF04) and resets the divider mid-instruction.
+fn push_onto_div() -> System {
+    System::new(Cartridge::new(rom_with_program(&[0x31, 0x05, 0xFF, 0xC5])).unwrap())
+}
+
+/// Park the timer at /16 with the internal divider at 12, so the selected
+/// bit (bit 3) falls 4 cycles into the next instruction.
+fn arm_timer(system: &mut System) {
+    system.mmu.timer.reset_div();
+    system.mmu.write(0xFF07, 0x05);
+    system.mmu.timer.step(12);
+}
+
+#[test]
+fn timer_increment_lands_mid_push_in_m_cycle_mode() {
+    // Whole-instruction stepping: the DIV reset happens "at" cycle 0 with
+    // the selected bit high (edge, TIMA=1), then 16 cycles of bus time walk
+    // the divider through another falling edge (TIMA=2).
+    let mut system = push_onto_div();
+    system.step().unwrap(); // LD SP,nn
+    arm_timer(&mut system);
+    system.step().unwrap(); // PUSH BC
+    assert_eq!(system.mmu.read(0xFF05), 2);
+
+    // M-cycle stepping: the divider's falling edge lands during the opcode
+    // fetch (TIMA=1), so by the time the PUSH writes DIV two M-cycles later
+    // the selected bit is low again and the reset clocks nothing.
+    let mut system = push_onto_div();
+    system.step().unwrap();
+    arm_timer(&mut system);
+    system.cpu.step_m_cycle(&mut system.mmu).unwrap();
+    assert_eq!(system.mmu.read(0xFF05), 1);
+}
+
+#[test]
+fn m_cycle_mode_matches_whole_instruction_results() {
+    // LD A,0x5A; LD (0xC010),A; LD HL,0xC010; LD B,(HL); PUSH BC; POP DE
+    let program = &[
+        0x3E, 0x5A, // LD A,0x5A
+        0xEA, 0x10, 0xC0, // LD (0xC010),A
+        0x21, 0x10, 0xC0, // LD HL,0xC010
+        0x46, // LD B,(HL)
+        0xC5, // PUSH BC
+        0xD1, // POP DE
+    ];
+    let mut whole = System::new(Cartridge::new(rom_with_program(program)).unwrap());
+    let mut micro = System::new(Cartridge::new(rom_with_program(program)).unwrap());
+    for _ in 0..6 {
+        whole.step().unwrap();
+        micro.cpu.step_m_cycle(&mut micro.mmu).unwrap();
+    }
+    assert_eq!(whole.cpu.regs.pc, micro.cpu.regs.pc);
+    assert_eq!(whole.cpu.regs.bc(), micro.cpu.regs.bc());
+    assert_eq!(whole.cpu.regs.de(), micro.cpu.regs.de());
+    assert_eq!(micro.cpu.regs.d, 0x5A, "POP DE got the value PUSH BC stored");
+}
@@ -0,0 +1,35 @@
+//! `Cpu::peek_cycles` must predict exactly what `step` then charges, for
+//! unconditional opcodes.
+
+use core_lib::{Cartridge, Cpu, System};
+use tests::rom_with_program;
+
+#[test]
+fn peek_matches_step_for_unconditional_opcodes() {
+    // A spread of lengths and addressing modes, plus two CB-prefixed ops.
+    let program = [
+        0x00, // NOP (4)
+        0x06, 0x12, // LD B,n (8)
+        0x21, 0x00, 0xC0, // LD HL,nn (12)
+        0x77, // LD (HL),A (8)
+        0x7E, // LD A,(HL) (8)
+        0x23, // INC HL (8)
+        0x80, // ADD A,B (4)
+        0xEA, 0x10, 0xC0, // LD (nn),A (16)
+        0xCB, 0x37, // SWAP A (8)
+        0xCB, 0x46, // BIT 0,(HL) (12)
+        0xC3, 0x00, 0x01, // JP nn (16)
+    ];
+    let rom = rom_with_program(&program);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    for _ in 0..program.len() {
+        let predicted = Cpu::peek_cycles(&system.mmu, system.cpu.regs.pc);
+        let actual = system.step().unwrap();
+        assert_eq!(
+            predicted as usize, actual,
+            "peek_cycles disagreed at {:#06X}",
+            system.cpu.regs.pc
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! POP AF must clear the low nibble of F — the flag register's bits 0–3
+//! don't physically exist, which blargg's instruction tests rely on.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+#[test]
+fn pop_af_masks_low_nibble_of_f() {
+    let rom = rom_with_program(&[
+        0x01, 0xFF, 0xFF, // LD BC,$FFFF
+        0xC5, // PUSH BC
+        0xF1, // POP AF
+    ]);
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    for _ in 0..3 {
+        system.step().unwrap();
+    }
+
+    assert_eq!(system.cpu.regs.a, 0xFF);
+    assert_eq!(system.cpu.regs.f, 0xF0, "F low nibble must read back as 0");
+    assert_eq!(system.cpu.regs.af(), 0xFFF0);
+}
@@ -0,0 +1,44 @@
+//! `System::run_frame` as a headless frame-producing API.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use core_lib::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+fn hash_frame(frame: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    frame.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn run_frame_returns_the_tile_filled_framebuffer() {
+    // Wait for VBlank (VRAM is writable there), fill tile 0 with 0xFF so
+    // every background pixel resolves to colour id 3, then spin.
+    let mut system = System::new(
+        Cartridge::new(rom_with_program(&[
+            0xF0, 0x44, // wait: LDH A,(LY)
+            0xFE, 0x90, //       CP 144
+            0x20, 0xFA, //       JR NZ, wait
+            0x21, 0x00, 0x80, // LD HL, 0x8000
+            0x3E, 0xFF, //       LD A, 0xFF
+            0x06, 0x10, //       LD B, 16
+            0x22, //       fill: LD (HL+), A
+            0x05, //             DEC B
+            0x20, 0xFC, //       JR NZ, fill
+            0x18, 0xFE, // spin: JR spin
+        ]))
+        .unwrap(),
+    );
+
+    // Frame 1 renders while the program is still waiting for VBlank; the
+    // fill lands during that frame's VBlank, so frame 2 shows it.
+    system.run_frame().unwrap();
+    let frame = system.run_frame().unwrap();
+    assert_eq!(frame.len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+
+    // Post-boot BGP is 0xFC, mapping colour id 3 to shade 3.
+    let expected = [3u8; SCREEN_WIDTH * SCREEN_HEIGHT];
+    assert_eq!(hash_frame(frame), hash_frame(&expected));
+}
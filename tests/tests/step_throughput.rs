@@ -0,0 +1,32 @@
+//! Step throughput sanity check.
+//!
+//! Before the per-step `println!` tracing was removed, a debug build managed
+//! roughly 60k steps/s because every step flushed stdout. With tracing off
+//! by default the same loop runs in the tens of millions of steps/s; the
+//! floor asserted here is deliberately far below that to stay robust on slow
+//! CI machines while still catching a reintroduced per-step print.
+
+use std::time::Instant;
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+#[test]
+fn steps_are_not_dominated_by_tracing() {
+    let rom = rom_with_program(&[0x18, 0xFE]); // JR -2
+    let mut system = System::new(Cartridge::new(rom).unwrap());
+
+    let steps = 200_000;
+    let start = Instant::now();
+    for _ in 0..steps {
+        system.step().unwrap();
+    }
+    let elapsed = start.elapsed();
+
+    let per_second = steps as f64 / elapsed.as_secs_f64();
+    println!("throughput: {per_second:.0} steps/s");
+    assert!(
+        per_second > 250_000.0,
+        "step throughput collapsed to {per_second:.0} steps/s — is per-step I/O back?"
+    );
+}
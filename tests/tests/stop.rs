@@ -0,0 +1,51 @@
+//! STOP instruction side effects.
+
+use core_lib::joypad::Button;
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+#[test]
+fn stop_resets_div() {
+    let mut system = System::new(Cartridge::new(rom_with_program(&[
+        0x10, 0x00, // STOP
+    ]))
+    .unwrap());
+
+    // Run the divider up before executing anything.
+    system.mmu.timer.step(0x4000);
+    assert_ne!(system.mmu.read(0xFF04), 0);
+
+    system.step().unwrap();
+    assert!(system.cpu.stopped);
+    assert_eq!(system.mmu.read(0xFF04), 0, "STOP zeroes DIV");
+}
+
+#[test]
+fn stop_stalls_the_machine_until_a_button_press() {
+    let mut system = System::new(Cartridge::new(rom_with_program(&[
+        0x3E, 0x10, // LD A,0x10 (select action buttons)
+        0xE0, 0x00, // LDH (0x00),A
+        0x10, 0x00, // STOP
+        0x00, // NOP
+    ]))
+    .unwrap());
+
+    for _ in 0..3 {
+        system.step().unwrap();
+    }
+    assert!(system.cpu.stopped);
+
+    // Stalled: the internal divider (and with it the timer, PPU and APU
+    // clocks) must not move.
+    let frozen = system.mmu.timer.internal_div();
+    for _ in 0..10 {
+        system.step().unwrap();
+    }
+    assert_eq!(system.mmu.timer.internal_div(), frozen);
+
+    // A press in the selected group wakes the machine up.
+    system.mmu.set_button(Button::A, true);
+    system.step().unwrap();
+    assert!(!system.cpu.stopped);
+    assert!(system.mmu.timer.internal_div() > frozen, "DIV resumed");
+}
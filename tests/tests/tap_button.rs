@@ -0,0 +1,31 @@
+//! `System::tap_button`: held for exactly one frame.
+
+use core_lib::joypad::Button;
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+#[test]
+fn tap_holds_the_button_for_one_frame_then_releases() {
+    // Select the action group, then spin copying FF00 into 0xC000 so the
+    // last value written reflects the joypad state during the frame.
+    let mut system = System::new(Cartridge::new(rom_with_program(&[
+        0x3E, 0x10, // LD A,0x10 (bit 5 low: action buttons)
+        0xE0, 0x00, // LDH (0x00),A
+        0xF0, 0x00, // loop: LDH A,(0x00)
+        0xEA, 0x00, 0xC0, // LD (0xC000),A
+        0x18, 0xF9, // JR loop
+    ]))
+    .unwrap());
+
+    system.tap_button(Button::A).unwrap();
+    assert_eq!(
+        system.mmu.read(0xC000) & 0x01,
+        0,
+        "A read as pressed during the tapped frame"
+    );
+    assert_eq!(
+        system.mmu.read(0xFF00) & 0x01,
+        0x01,
+        "A released once the tap returns"
+    );
+}
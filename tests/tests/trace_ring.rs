@@ -0,0 +1,35 @@
+//! The CPU's post-mortem instruction ring buffer.
+
+use core_lib::{Cartridge, System};
+use tests::rom_with_program;
+
+#[test]
+fn ring_holds_the_instructions_leading_up_to_an_error() {
+    let mut system = System::new(Cartridge::new(rom_with_program(&[
+        0x00, // NOP
+        0x3E, 0x42, // LD A,0x42
+        0x04, // INC B
+        0xFD, // illegal opcode
+    ]))
+    .unwrap());
+    system.cpu.enable_trace_ring(3);
+
+    for _ in 0..3 {
+        system.step().unwrap();
+    }
+    let err = system.step().unwrap_err();
+    assert!(err.to_string().contains("0xFD"), "unexpected error: {err}");
+
+    // Capacity 3: the NOP has been evicted; the failing opcode is last.
+    assert_eq!(
+        system.cpu.recent_instructions(),
+        vec![(0x0101, 0x3E), (0x0103, 0x04), (0x0104, 0xFD)]
+    );
+}
+
+#[test]
+fn ring_is_empty_until_enabled() {
+    let mut system = System::new(Cartridge::new(rom_with_program(&[0x00])).unwrap());
+    system.step().unwrap();
+    assert!(system.cpu.recent_instructions().is_empty());
+}